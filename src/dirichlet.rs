@@ -0,0 +1,306 @@
+use std::ops::Index;
+
+use crate::{PrimeFactorSieve, SieveInt};
+
+/// The values f(1), ..., f(n) of an integer-valued arithmetic function, which
+/// can be combined with Dirichlet convolution.
+///
+/// Entry 0 is unused and always 0, so `table[n]` is f(n).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArithmeticTable {
+  values: Vec<i64>,
+}
+
+impl ArithmeticTable {
+  /// Builds a table from `values`, where `values[n]` is f(n). The value at
+  /// index 0 is ignored.
+  pub fn new(mut values: Vec<i64>) -> Self {
+    if values.is_empty() {
+      values.push(0);
+    }
+    values[0] = 0;
+    Self { values }
+  }
+
+  /// Builds the table of f(1), ..., f(n).
+  pub fn from_fn(n: usize, f: impl FnMut(usize) -> i64) -> Self {
+    Self::new([0].into_iter().chain((1..=n).map(f)).collect())
+  }
+
+  /// The unit of Dirichlet convolution, ε(n) = 1 if n is 1 and 0 otherwise.
+  pub fn identity(n: usize) -> Self {
+    Self::from_fn(n, |k| (k == 1) as i64)
+  }
+
+  /// The constant function 1(n) = 1.
+  pub fn one(n: usize) -> Self {
+    Self::from_fn(n, |_| 1)
+  }
+
+  /// The identity function id(n) = n.
+  pub fn id(n: usize) -> Self {
+    Self::from_fn(n, |k| k as i64)
+  }
+
+  /// The largest n in the table.
+  pub fn bound(&self) -> usize {
+    self.values.len() - 1
+  }
+
+  /// The values f(0), ..., f(bound), with f(0) = 0.
+  pub fn values(&self) -> &[i64] {
+    &self.values
+  }
+
+  /// Returns the Dirichlet convolution (f * g)(n) = sum_{d | n} f(d) g(n / d)
+  /// up to the smaller of the two bounds, in O(N log N) time.
+  pub fn convolve(&self, other: &Self) -> Self {
+    let n = self.bound().min(other.bound());
+    let mut values = vec![0; n + 1];
+    for d in 1..=n {
+      let f = self.values[d];
+      if f == 0 {
+        continue;
+      }
+      for (k, m) in (d..=n).step_by(d).enumerate() {
+        values[m] += f * other.values[k + 1];
+      }
+    }
+    Self { values }
+  }
+
+  /// Returns the Dirichlet inverse g with f * g = ε, or `None` if f(1) is not
+  /// ±1, in which case the inverse does not have integer values.
+  pub fn inverse(&self) -> Option<Self> {
+    let n = self.bound();
+    if n == 0 {
+      return Some(self.clone());
+    }
+    let f1 = self.values[1];
+    if f1.abs() != 1 {
+      return None;
+    }
+
+    // Holds (f * g)(m) over the divisors of m processed so far, so that g(d)
+    // is known once all proper divisors of d have been processed.
+    let mut values = vec![0; n + 1];
+    values[1] = 1;
+    for d in 1..=n {
+      // f(1) g(d) = ε(d) - sum_{e | d, e < d} f(d / e) g(e).
+      let g = values[d] * f1;
+      values[d] = g;
+      if g == 0 {
+        continue;
+      }
+      for (k, m) in (2 * d..=n).step_by(d).enumerate() {
+        values[m] -= self.values[k + 2] * g;
+      }
+    }
+    Some(Self { values })
+  }
+
+  /// Recovers f from its summatory function F(n) = sum_{d | n} f(d), which is
+  /// the convolution F * μ, in O(N log N) time.
+  pub fn mobius_inversion(&self) -> Self {
+    let mut values = self.values.clone();
+    let n = self.bound();
+    for d in 1..=n {
+      let f = values[d];
+      if f == 0 {
+        continue;
+      }
+      for m in (2 * d..=n).step_by(d) {
+        values[m] -= f;
+      }
+    }
+    Self { values }
+  }
+}
+
+impl Index<usize> for ArithmeticTable {
+  type Output = i64;
+
+  fn index(&self, n: usize) -> &i64 {
+    &self.values[n]
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the Dirichlet convolution f * g of two multiplicative functions
+  /// with f(1) = g(1) = 1, up to the smaller of the two bounds, which must be
+  /// within the sieve bound. Only the values at prime powers are convolved directly and the
+  /// rest follow from multiplicativity, so this runs in roughly linear time.
+  pub fn convolve_multiplicative(
+    &self,
+    f: &ArithmeticTable,
+    g: &ArithmeticTable,
+  ) -> ArithmeticTable {
+    let n = f.bound().min(g.bound());
+    assert!(
+      n == 0 || (f[1] == 1 && g[1] == 1),
+      "multiplicative functions have f(1) = 1"
+    );
+    self.extend_multiplicative(n, |_, p, pk| {
+      // (f * g)(p^k) = sum_{i=0..k} f(p^i) g(p^k / p^i).
+      let mut sum = g[pk];
+      let mut pi = 1;
+      while pi < pk {
+        pi *= p;
+        sum += f[pi] * g[pk / pi];
+      }
+      sum
+    })
+  }
+
+  /// Returns the Dirichlet inverse of a multiplicative function f with
+  /// f(1) = 1, which is also multiplicative, in roughly linear time. The
+  /// table must be within the sieve bound.
+  pub fn inverse_multiplicative(&self, f: &ArithmeticTable) -> ArithmeticTable {
+    let n = f.bound();
+    assert!(
+      n == 0 || f[1] == 1,
+      "multiplicative functions have f(1) = 1"
+    );
+    self.extend_multiplicative(n, |values, p, pk| {
+      // g(p^k) = -sum_{i=1..k} f(p^i) g(p^k / p^i).
+      let mut sum = 0;
+      let mut pi = 1;
+      while pi < pk {
+        pi *= p;
+        sum += f[pi] * values[pk / pi];
+      }
+      -sum
+    })
+  }
+
+  /// Builds the multiplicative function with h(1) = 1 and h(p^k) given by
+  /// `prime_power(values, p, p^k)`, where `values` holds h at all smaller n.
+  fn extend_multiplicative(
+    &self,
+    n: usize,
+    mut prime_power: impl FnMut(&[i64], usize, usize) -> i64,
+  ) -> ArithmeticTable {
+    assert!(
+      n <= self.bound().as_usize(),
+      "table bound {n} exceeds the sieve bound {}",
+      self.bound()
+    );
+    let mut values = vec![0; n + 1];
+    if n >= 1 {
+      values[1] = 1;
+    }
+    for k in 2..=n {
+      let (p, _, m) = self.split_prime_power(k);
+      values[k] = if m == 1 {
+        prime_power(&values, p, k)
+      } else {
+        values[k / m] * values[m]
+      };
+    }
+    ArithmeticTable { values }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Mobius, PrimeFactorSieve, Totient};
+
+  use super::ArithmeticTable;
+
+  const N: usize = 10_000;
+
+  fn mobius(sieve: &PrimeFactorSieve) -> ArithmeticTable {
+    ArithmeticTable::new(
+      sieve
+        .multiplicative_table(&Mobius)
+        .into_iter()
+        .map(i64::from)
+        .collect(),
+    )
+  }
+
+  fn totient(sieve: &PrimeFactorSieve) -> ArithmeticTable {
+    ArithmeticTable::new(
+      sieve
+        .multiplicative_table(&Totient)
+        .into_iter()
+        .map(|phi| phi as i64)
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn test_convolve() {
+    let sieve = PrimeFactorSieve::new(N as u32);
+    let (mu, phi, one) = (mobius(&sieve), totient(&sieve), ArithmeticTable::one(N));
+    assert_eq!(mu.convolve(&one), ArithmeticTable::identity(N));
+    assert_eq!(phi.convolve(&one), ArithmeticTable::id(N));
+    assert_eq!(ArithmeticTable::id(N).convolve(&mu), phi);
+
+    // 1 * 1 is the number of divisors.
+    let tau = one.convolve(&one);
+    for n in 1..=N {
+      assert_eq!(tau[n], sieve.factors_count(n as u32) as i64);
+    }
+
+    assert_eq!(mu.convolve(&ArithmeticTable::one(10)).bound(), 10);
+  }
+
+  #[test]
+  fn test_inverse() {
+    let sieve = PrimeFactorSieve::new(N as u32);
+    let mu = mobius(&sieve);
+    assert_eq!(ArithmeticTable::one(N).inverse(), Some(mu.clone()));
+    assert_eq!(mu.inverse(), Some(ArithmeticTable::one(N)));
+
+    let f = ArithmeticTable::from_fn(N, |n| (n as i64 % 7) - 2);
+    assert_eq!(
+      f.convolve(&f.inverse().unwrap()),
+      ArithmeticTable::identity(N)
+    );
+    assert_eq!(
+      ArithmeticTable::from_fn(N, |n| n as i64 + 1).inverse(),
+      None
+    );
+    assert_eq!(ArithmeticTable::new(vec![]).inverse().unwrap().bound(), 0);
+  }
+
+  #[test]
+  fn test_mobius_inversion() {
+    let sieve = PrimeFactorSieve::new(N as u32);
+    let id = ArithmeticTable::id(N);
+    let sigma = id.convolve(&ArithmeticTable::one(N));
+    assert_eq!(sigma.mobius_inversion(), id);
+    assert_eq!(sigma.mobius_inversion(), sigma.convolve(&mobius(&sieve)));
+
+    let f = ArithmeticTable::from_fn(N, |n| (n as i64 * 31) % 17 - 8);
+    assert_eq!(f.convolve(&ArithmeticTable::one(N)).mobius_inversion(), f);
+  }
+
+  #[test]
+  fn test_multiplicative() {
+    let sieve = PrimeFactorSieve::new(N as u32);
+    let (mu, phi, id) = (mobius(&sieve), totient(&sieve), ArithmeticTable::id(N));
+    let one = ArithmeticTable::one(N);
+    for (f, g) in [
+      (&mu, &one),
+      (&phi, &one),
+      (&id, &mu),
+      (&phi, &phi),
+      (&id, &id),
+    ] {
+      assert_eq!(sieve.convolve_multiplicative(f, g), f.convolve(g));
+    }
+    for f in [&mu, &phi, &id, &one] {
+      assert_eq!(Some(sieve.inverse_multiplicative(f)), f.inverse());
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "multiplicative functions have f(1) = 1")]
+  fn test_convolve_not_multiplicative() {
+    let sieve = PrimeFactorSieve::new(10);
+    let doubled = ArithmeticTable::new((0..=10).map(|n| 2 * n as i64).collect());
+    sieve.convolve_multiplicative(&ArithmeticTable::one(10), &doubled);
+  }
+}
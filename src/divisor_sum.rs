@@ -0,0 +1,122 @@
+use crate::{PrimeFactorSieve, SieveError, SieveInt};
+
+/// Returns σ_k(p^e) = 1 + p^k + p^2k + ... + p^ek, or `None` on overflow.
+fn prime_power_divisor_sum<T: SieveInt>(p: u64, e: u32, k: u32) -> Option<T> {
+  let pk = T::try_from_u64(p)?.checked_pow(k)?;
+  (0..e).try_fold(T::ONE, |sum, _| sum.checked_mul(pk)?.checked_add(T::ONE))
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the divisor function σ_k(n), the sum of the k-th powers of all
+  /// divisors of n, computed from the prime factorization of n. Panics if n is
+  /// 0 or out of range, or if the result overflows `T`.
+  pub fn divisor_sum<T: SieveInt>(&self, n: Q, k: u32) -> T {
+    self
+      .try_divisor_sum(n, k)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_divisor_sum<T: SieveInt>(&self, n: Q, k: u32) -> Result<T, SieveError> {
+    self.try_prime_factors(n)?.try_fold(T::ONE, |sum, (p, e)| {
+      prime_power_divisor_sum(p.as_u64(), e, k)
+        .and_then(|term| sum.checked_mul(term))
+        .ok_or(SieveError::Overflow)
+    })
+  }
+
+  /// Returns σ_k(n) for all n <= bound, with σ_k(0) = 0. Panics if any value
+  /// overflows `T`.
+  pub fn divisor_sum_table<T: SieveInt>(&self, k: u32) -> Vec<T> {
+    let bound = self.bound().as_usize();
+    let mut sums = vec![T::ZERO; bound + 1];
+    if bound >= 1 {
+      sums[1] = T::ONE;
+    }
+    for n in 2..=bound {
+      let (p, e, m) = self.split_prime_power(n);
+      sums[n] = prime_power_divisor_sum::<T>(p as u64, e, k)
+        .and_then(|term| term.checked_mul(sums[m]))
+        .unwrap_or_else(|| panic!("{}", SieveError::Overflow));
+    }
+    sums
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Ordering;
+
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, SieveError};
+
+  #[test]
+  fn test_divisor_sum() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    for n in 1..=1_000 {
+      for k in 0..4 {
+        let expected = sieve.factors(n).map(|d| (d as u128).pow(k)).sum::<u128>();
+        assert_eq!(sieve.divisor_sum::<u128>(n, k), expected);
+      }
+      assert_eq!(sieve.divisor_sum::<u32>(n, 0), sieve.factors_count(n));
+    }
+    assert_eq!(sieve.try_divisor_sum::<u64>(0, 1), Err(SieveError::Zero));
+  }
+
+  #[test]
+  fn test_overflow() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.try_divisor_sum::<u16>(720, 1), Ok(2_418));
+    assert_eq!(
+      sieve.try_divisor_sum::<u16>(720, 2),
+      Err(SieveError::Overflow)
+    );
+    assert_eq!(
+      sieve.try_divisor_sum::<u64>(997, 7),
+      Err(SieveError::Overflow)
+    );
+    assert_eq!(
+      sieve.try_divisor_sum::<u128>(997, 7),
+      Ok(1 + 997_u128.pow(7))
+    );
+  }
+
+  #[test]
+  fn test_divisor_sum_table() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    for k in 0..3 {
+      let table = sieve.divisor_sum_table::<u64>(k);
+      assert_eq!(table.len(), 10_001);
+      assert_eq!(table[0], 0);
+      for n in 1..=10_000 {
+        assert_eq!(table[n as usize], sieve.divisor_sum::<u64>(n, k));
+      }
+    }
+  }
+
+  #[test]
+  fn test_perfect_numbers() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    let sigma = sieve.divisor_sum_table::<u64>(1);
+    let classes = (1..=10_000)
+      .map(|n| sigma[n].cmp(&(2 * n as u64)))
+      .collect_vec();
+    let with_class = |class| {
+      (1..=10_000)
+        .filter(|&n| classes[n - 1] == class)
+        .collect_vec()
+    };
+
+    assert_eq!(with_class(Ordering::Equal), vec![6, 28, 496, 8128]);
+    assert_eq!(
+      with_class(Ordering::Greater)
+        .into_iter()
+        .take_while(|&n| n <= 100)
+        .collect_vec(),
+      vec![
+        12, 18, 20, 24, 30, 36, 40, 42, 48, 54, 56, 60, 66, 70, 72, 78, 80, 84, 88, 90, 96, 100
+      ]
+    );
+    assert_eq!(with_class(Ordering::Less).len(), 10_000 - 4 - 2_488);
+  }
+}
@@ -0,0 +1,377 @@
+use std::{
+  cmp::Reverse,
+  collections::BinaryHeap,
+  iter::FusedIterator,
+  ops::{Bound, RangeBounds},
+};
+
+use crate::{PrimeFactorSieve, SieveError, SieveInt};
+
+/// The largest number of distinct prime factors of any n < 2^64, as the
+/// product of the first 16 primes exceeds 2^64.
+const MAX_DISTINCT_PRIMES: usize = 15;
+
+/// An iterator over all divisors of a number, which steps through the
+/// exponents of its prime factors like an odometer without allocating.
+///
+/// Divisors are produced with the exponent of the smallest prime changing
+/// slowest, so e.g. the divisors of 12 are 1, 3, 2, 6, 4, 12.
+#[derive(Clone, Debug)]
+pub struct Divisors<Q> {
+  primes: [Q; MAX_DISTINCT_PRIMES],
+  /// The multiplicity of each prime factor.
+  multiplicities: [u32; MAX_DISTINCT_PRIMES],
+  /// The exponent of each prime factor in the next divisor.
+  exponents: [u32; MAX_DISTINCT_PRIMES],
+  /// products[i] is the product of the first i prime powers of the next
+  /// divisor, so products[len] is the next divisor.
+  products: [Q; MAX_DISTINCT_PRIMES + 1],
+  len: usize,
+  remaining: usize,
+}
+
+impl<Q: SieveInt> Divisors<Q> {
+  pub(crate) fn new(prime_factors: impl Iterator<Item = (Q, u32)>) -> Self {
+    let mut divisors = Self {
+      primes: [Q::ONE; MAX_DISTINCT_PRIMES],
+      multiplicities: [0; MAX_DISTINCT_PRIMES],
+      exponents: [0; MAX_DISTINCT_PRIMES],
+      products: [Q::ONE; MAX_DISTINCT_PRIMES + 1],
+      len: 0,
+      remaining: 1,
+    };
+    for (p, multiplicity) in prime_factors {
+      divisors.primes[divisors.len] = p;
+      divisors.multiplicities[divisors.len] = multiplicity;
+      divisors.len += 1;
+      divisors.remaining *= multiplicity as usize + 1;
+    }
+    divisors
+  }
+
+  /// Advances to the next divisor which is at most `limit`, skipping every
+  /// combination of later exponents once a prefix of the divisor exceeds it.
+  /// Returns false if there are none left.
+  fn advance(&mut self, limit: Q) -> bool {
+    let next = (0..self.len).rfind(|&i| {
+      self.exponents[i] < self.multiplicities[i]
+        && self.products[i + 1]
+          .checked_mul(self.primes[i])
+          .is_some_and(|product| product <= limit)
+    });
+    let Some(i) = next else {
+      return false;
+    };
+    self.exponents[i] += 1;
+    self.products[i + 1] *= self.primes[i];
+    for j in i + 1..self.len {
+      self.exponents[j] = 0;
+      self.products[j + 1] = self.products[i + 1];
+    }
+    true
+  }
+}
+
+impl<Q: SieveInt> Iterator for Divisors<Q> {
+  type Item = Q;
+
+  fn next(&mut self) -> Option<Q> {
+    if self.remaining == 0 {
+      return None;
+    }
+    self.remaining -= 1;
+    let divisor = self.products[self.len];
+
+    // Advance the last exponent which has not reached its multiplicity, and
+    // reset all exponents after it.
+    self.advance(Q::MAX);
+    Some(divisor)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.remaining, Some(self.remaining))
+  }
+}
+
+impl<Q: SieveInt> ExactSizeIterator for Divisors<Q> {}
+
+impl<Q: SieveInt> FusedIterator for Divisors<Q> {}
+
+/// An iterator over the divisors of a number which are at most some limit, in
+/// the same order as `Divisors`. Exponent combinations are pruned as soon as
+/// their product exceeds the limit, so divisors above it are never visited.
+#[derive(Clone, Debug)]
+pub struct DivisorsUpTo<Q> {
+  divisors: Divisors<Q>,
+  limit: Q,
+  done: bool,
+}
+
+impl<Q: SieveInt> Iterator for DivisorsUpTo<Q> {
+  type Item = Q;
+
+  fn next(&mut self) -> Option<Q> {
+    if self.done {
+      return None;
+    }
+    let divisor = self.divisors.products[self.divisors.len];
+    self.done = !self.divisors.advance(self.limit);
+    Some(divisor)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    match self.done {
+      true => (0, Some(0)),
+      false => (1, Some(self.divisors.remaining)),
+    }
+  }
+}
+
+impl<Q: SieveInt> FusedIterator for DivisorsUpTo<Q> {}
+
+/// An iterator over all divisors of a number in ascending order.
+///
+/// Each divisor is reached from a unique smaller divisor by multiplying by a
+/// prime no smaller than its largest prime factor, and the pending divisors
+/// are kept in a min-heap, so at most one entry per distinct prime is added
+/// per divisor produced.
+#[derive(Clone, Debug)]
+pub struct SortedDivisors<Q> {
+  primes: [Q; MAX_DISTINCT_PRIMES],
+  multiplicities: [u32; MAX_DISTINCT_PRIMES],
+  len: usize,
+  /// Pending divisors d, with the index of the largest prime p dividing d
+  /// plus one (or 0 for d = 1) and the multiplicity of p in d.
+  heap: BinaryHeap<Reverse<(Q, usize, u32)>>,
+  remaining: usize,
+}
+
+impl<Q: SieveInt> SortedDivisors<Q> {
+  fn new(prime_factors: impl Iterator<Item = (Q, u32)>) -> Self {
+    let divisors = Divisors::new(prime_factors);
+    Self {
+      primes: divisors.primes,
+      multiplicities: divisors.multiplicities,
+      len: divisors.len,
+      heap: BinaryHeap::from([Reverse((Q::ONE, 0, 0))]),
+      remaining: divisors.remaining,
+    }
+  }
+}
+
+impl<Q: SieveInt> Iterator for SortedDivisors<Q> {
+  type Item = Q;
+
+  fn next(&mut self) -> Option<Q> {
+    let Reverse((d, last, exponent)) = self.heap.pop()?;
+    self.remaining -= 1;
+    if last > 0 && exponent < self.multiplicities[last - 1] {
+      self
+        .heap
+        .push(Reverse((d * self.primes[last - 1], last, exponent + 1)));
+    }
+    for i in last..self.len {
+      self.heap.push(Reverse((d * self.primes[i], i + 1, 1)));
+    }
+    Some(d)
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    (self.remaining, Some(self.remaining))
+  }
+}
+
+impl<Q: SieveInt> ExactSizeIterator for SortedDivisors<Q> {}
+
+impl<Q: SieveInt> FusedIterator for SortedDivisors<Q> {}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns an iterator over all factors of n. Panics if n is 0 or out of
+  /// range.
+  pub fn factors(&self, n: Q) -> Divisors<Q> {
+    self.try_factors(n).unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factors(&self, n: Q) -> Result<Divisors<Q>, SieveError> {
+    Ok(Divisors::new(self.try_prime_factors(n)?))
+  }
+
+  /// Returns an iterator over all factors of n in ascending order. Panics if n
+  /// is 0 or out of range.
+  pub fn factors_sorted(&self, n: Q) -> SortedDivisors<Q> {
+    self
+      .try_factors_sorted(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factors_sorted(&self, n: Q) -> Result<SortedDivisors<Q>, SieveError> {
+    Ok(SortedDivisors::new(self.try_prime_factors(n)?))
+  }
+
+  /// Returns an iterator over all factors of n which are <= `limit`, in the
+  /// same order as `factors`. Panics if n is 0 or out of range.
+  pub fn factors_le(&self, n: Q, limit: Q) -> DivisorsUpTo<Q> {
+    self
+      .try_factors_le(n, limit)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factors_le(&self, n: Q, limit: Q) -> Result<DivisorsUpTo<Q>, SieveError> {
+    Ok(DivisorsUpTo {
+      divisors: Divisors::new(self.try_prime_factors(n)?),
+      limit,
+      done: limit == Q::ZERO,
+    })
+  }
+
+  /// Returns an iterator over all factors of n in `range`, in the same order
+  /// as `factors`. Panics if n is 0 or out of range.
+  pub fn factors_in<R: RangeBounds<Q>>(
+    &self,
+    n: Q,
+    range: R,
+  ) -> impl Iterator<Item = Q> + use<S, Q, R> {
+    self
+      .try_factors_in(n, range)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factors_in<R: RangeBounds<Q>>(
+    &self,
+    n: Q,
+    range: R,
+  ) -> Result<impl Iterator<Item = Q> + use<S, Q, R>, SieveError> {
+    let limit = match range.end_bound() {
+      Bound::Included(&end) => end,
+      Bound::Excluded(&end) if end == Q::ZERO => Q::ZERO,
+      Bound::Excluded(&end) => end - Q::ONE,
+      Bound::Unbounded => Q::MAX,
+    };
+    Ok(
+      self
+        .try_factors_le(n, limit)?
+        .filter(move |d| range.contains(d)),
+    )
+  }
+
+  /// Replaces the contents of `factors` with all factors of n, in no
+  /// particular order, reusing its allocation. Panics if n is 0 or out of
+  /// range.
+  pub fn factors_into(&self, n: Q, factors: &mut Vec<Q>) {
+    self
+      .try_factors_into(n, factors)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factors_into(&self, n: Q, factors: &mut Vec<Q>) -> Result<(), SieveError> {
+    let prime_factors = self.try_prime_factors(n)?;
+    factors.clear();
+    factors.push(Q::ONE);
+    for (p, multiplicity) in prime_factors {
+      // Multiply every divisor found so far by p, p^2, ..., p^multiplicity.
+      let len = factors.len();
+      let mut power = Q::ONE;
+      for _ in 0..multiplicity {
+        power *= p;
+        for i in 0..len {
+          factors.push(factors[i] * power);
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeFactorSieveBuilder, SieveError};
+
+  #[test]
+  fn test_divisors_order() {
+    let sieve = PrimeFactorSieve::new(100);
+    assert_eq!(sieve.factors(1).collect_vec(), vec![1]);
+    assert_eq!(sieve.factors(12).collect_vec(), vec![1, 3, 2, 6, 4, 12]);
+    assert_eq!(sieve.factors(97).collect_vec(), vec![1, 97]);
+
+    let mut divisors = sieve.factors(60);
+    assert_eq!(divisors.len(), 12);
+    divisors.nth(4);
+    assert_eq!(divisors.len(), 7);
+    assert_eq!(divisors.by_ref().count(), 7);
+    assert_eq!(divisors.next(), None);
+  }
+
+  #[test]
+  fn test_divisors_brute_force() {
+    let sieve = PrimeFactorSieve::new(5_000);
+    let mut buffer = Vec::new();
+    for n in 1..=5_000 {
+      let expected = (1..=n).filter(|d| n % d == 0).collect_vec();
+      assert_eq!(sieve.factors(n).sorted().collect_vec(), expected);
+      sieve.factors_into(n, &mut buffer);
+      buffer.sort();
+      assert_eq!(buffer, expected);
+    }
+    assert_eq!(sieve.try_factors(0).err(), Some(SieveError::Zero));
+    assert_eq!(
+      sieve.try_factors_into(5_001, &mut buffer),
+      Err(SieveError::OutOfRange { n: 5_001, max: 5_000 })
+    );
+  }
+
+  #[test]
+  fn test_sorted() {
+    let sieve = PrimeFactorSieve::new(5_000);
+    for n in 1..=5_000 {
+      let sorted = sieve.factors_sorted(n);
+      assert_eq!(sorted.len(), sieve.factors_count(n) as usize);
+      assert!(sorted.eq(sieve.factors(n).sorted()));
+    }
+    assert_eq!(
+      sieve.factors_sorted(360).collect_vec(),
+      vec![
+        1, 2, 3, 4, 5, 6, 8, 9, 10, 12, 15, 18, 20, 24, 30, 36, 40, 45, 60, 72, 90, 120, 180, 360
+      ]
+    );
+    assert_eq!(sieve.try_factors_sorted(0).err(), Some(SieveError::Zero));
+  }
+
+  #[test]
+  fn test_bounded() {
+    let sieve = PrimeFactorSieve::new(5_000);
+    for n in 1..=5_000_u32 {
+      for limit in [0, 1, 2, n.isqrt(), n / 3, n, u32::MAX] {
+        assert!(
+          sieve
+            .factors_le(n, limit)
+            .eq(sieve.factors(n).filter(|&d| d <= limit))
+        );
+      }
+      assert!(
+        sieve
+          .factors_in(n, 10..100)
+          .eq(sieve.factors(n).filter(|d| (10..100).contains(d)))
+      );
+    }
+
+    // The largest divisor <= sqrt(n).
+    assert_eq!(sieve.factors_le(4_998, 70).max(), Some(51));
+    assert_eq!(sieve.factors_in(360, ..=10).count(), 9);
+    assert_eq!(sieve.factors_in(360, 20..).count(), 12);
+    assert_eq!(sieve.factors_in(360, ..0).next(), None);
+    assert_eq!(sieve.try_factors_le(0, 10).err(), Some(SieveError::Zero));
+  }
+
+  #[test]
+  fn test_many_distinct_primes() {
+    // The product of the first 8 primes, which has 2^8 divisors.
+    let n = 9_699_690;
+    let sieve: PrimeFactorSieve<u32, u64> = PrimeFactorSieveBuilder::new(n).build();
+    let divisors = sieve.factors(n).collect_vec();
+    assert_eq!(divisors.len(), 256);
+    assert!(divisors.iter().all(|&d| n % d == 0));
+    assert_eq!(divisors.iter().unique().count(), 256);
+  }
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, fmt::Display};
+
+/// An error returned by a query which a sieve cannot answer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SieveError {
+  /// The input is 0, which has no prime factorization.
+  Zero,
+  /// The input is 1, which has no prime factors.
+  One,
+  /// The input is larger than `max`, the largest input the query supports.
+  OutOfRange { n: u64, max: u64 },
+  /// The result does not fit in the output type.
+  Overflow,
+}
+
+impl Display for SieveError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Zero => write!(f, "0 has no prime factorization"),
+      Self::One => write!(f, "1 has no prime factors"),
+      Self::OutOfRange { n, max } => {
+        write!(
+          f,
+          "{n} is out of range, the maximum supported input is {max}"
+        )
+      }
+      Self::Overflow => write!(f, "result does not fit in the output type"),
+    }
+  }
+}
+
+impl Error for SieveError {}
@@ -0,0 +1,203 @@
+use crate::{Factorization, PrimeFactorSieve, SieveError, SieveInt};
+
+/// Returns v_p(n!), the exponent of the prime p in n!, by Legendre's formula
+/// n/p + n/p^2 + n/p^3 + .... Panics if p < 2.
+pub fn factorial_valuation(n: u64, p: u64) -> u64 {
+  assert!(p >= 2, "{p} is not a prime");
+  let mut valuation = 0;
+  let mut n = n;
+  while n >= p {
+    n /= p;
+    valuation += n;
+  }
+  valuation
+}
+
+/// Returns v_p(C(n, k)) for k <= n, which by Kummer's theorem is the number of
+/// carries when adding k and n - k in base p.
+fn binomial_valuation(n: u64, k: u64, p: u64) -> u64 {
+  let (mut a, mut b) = (k, n - k);
+  let mut carry = 0;
+  let mut carries = 0;
+  while a > 0 || b > 0 {
+    carry = u64::from(a % p + b % p + carry >= p);
+    carries += carry;
+    a /= p;
+    b /= p;
+  }
+  carries
+}
+
+fn exponent(valuation: u64) -> Result<u32, SieveError> {
+  u32::try_from(valuation).map_err(|_| SieveError::Overflow)
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the factorization of n!. Panics if n is out of range.
+  pub fn factorial_factorization(&self, n: Q) -> Factorization {
+    self
+      .try_factorial_factorization(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factorial_factorization(&self, n: Q) -> Result<Factorization, SieveError> {
+    self.check_in_range(n)?;
+    self
+      .primes_in(..=n)
+      .map(|p| {
+        Ok((
+          p.as_u64(),
+          exponent(factorial_valuation(n.as_u64(), p.as_u64()))?,
+        ))
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map(Factorization::from_prime_powers_unchecked)
+  }
+
+  /// Returns the factorization of the binomial coefficient C(n, k). Panics if
+  /// k > n, since C(n, k) is then 0, or if n is out of range.
+  pub fn binomial_factorization(&self, n: Q, k: Q) -> Factorization {
+    self
+      .try_binomial_factorization(n, k)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_binomial_factorization(&self, n: Q, k: Q) -> Result<Factorization, SieveError> {
+    self.check_in_range(n)?;
+    if k > n {
+      return Err(SieveError::Zero);
+    }
+    self
+      .primes_in(..=n)
+      .map(|p| {
+        let valuation = binomial_valuation(n.as_u64(), k.as_u64(), p.as_u64());
+        Ok((p.as_u64(), exponent(valuation)?))
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map(Factorization::from_prime_powers_unchecked)
+  }
+
+  /// Returns the factorization of the multinomial coefficient
+  /// (k_1 + ... + k_m)! / (k_1! ... k_m!). Panics if the sum of `ks` is out of
+  /// range.
+  pub fn multinomial_factorization(&self, ks: &[Q]) -> Factorization {
+    self
+      .try_multinomial_factorization(ks)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_multinomial_factorization(&self, ks: &[Q]) -> Result<Factorization, SieveError> {
+    let max = self.bound().as_u64();
+    let n = ks
+      .iter()
+      .map(|k| k.as_u64())
+      .try_fold(0u64, |n, k| n.checked_add(k))
+      .filter(|&n| n <= max)
+      .ok_or_else(|| SieveError::OutOfRange {
+        n: ks.iter().fold(0u64, |n, k| n.saturating_add(k.as_u64())),
+        max,
+      })?;
+    self
+      .primes_in(..)
+      .take_while(|p| p.as_u64() <= n)
+      .map(|p| {
+        let p = p.as_u64();
+        let valuation = factorial_valuation(n, p)
+          - ks
+            .iter()
+            .map(|k| factorial_valuation(k.as_u64(), p))
+            .sum::<u64>();
+        Ok((p, exponent(valuation)?))
+      })
+      .collect::<Result<Vec<_>, _>>()
+      .map(Factorization::from_prime_powers_unchecked)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Factorization, PrimeFactorSieve, SieveError};
+
+  use super::factorial_valuation;
+
+  #[test]
+  fn test_factorial_valuation() {
+    assert_eq!(factorial_valuation(0, 2), 0);
+    assert_eq!(factorial_valuation(10, 2), 8);
+    assert_eq!(factorial_valuation(100, 5), 24);
+    assert_eq!(factorial_valuation(1_000_000, 7), 166_664);
+    assert_eq!(factorial_valuation(u64::MAX, 2), u64::MAX - 64);
+  }
+
+  #[test]
+  fn test_factorial_factorization() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    let mut factorial = 1u64;
+    for n in 0..=20 {
+      if n > 0 {
+        factorial *= n as u64;
+      }
+      assert_eq!(sieve.factorial_factorization(n).value(), factorial);
+    }
+    assert!(sieve.factorial_factorization(1).is_one());
+
+    let f = sieve.factorial_factorization(1_000);
+    assert_eq!(f.prime_factors().len(), 168);
+    assert_eq!(f.prime_factors()[0], (2, 994));
+    assert_eq!(f.prime_factors()[167], (997, 1));
+    assert_eq!(
+      sieve.try_factorial_factorization(1_001),
+      Err(SieveError::OutOfRange { n: 1_001, max: 1_000 })
+    );
+  }
+
+  #[test]
+  fn test_binomial_factorization() {
+    let sieve = PrimeFactorSieve::new(500);
+    let mut row = vec![1u128];
+    for n in 0..=120 {
+      for (k, &c) in row.iter().enumerate() {
+        let f = sieve.binomial_factorization(n, k as u32);
+        assert_eq!(f.try_value_u128(), Ok(c), "C({n}, {k})");
+      }
+      row = (0..=row.len())
+        .map(|k| {
+          if k == 0 || k == row.len() {
+            1
+          } else {
+            row[k - 1] + row[k]
+          }
+        })
+        .collect();
+    }
+
+    // C(n, k) = n! / (k! (n - k)!) well beyond what fits in a u128.
+    for (n, k) in [(500, 250), (500, 1), (499, 123)] {
+      assert_eq!(
+        sieve.binomial_factorization(n, k),
+        sieve.factorial_factorization(n)
+          / (sieve.factorial_factorization(k) * sieve.factorial_factorization(n - k))
+      );
+    }
+    assert_eq!(
+      sieve.try_binomial_factorization(5, 6),
+      Err(SieveError::Zero)
+    );
+  }
+
+  #[test]
+  fn test_multinomial_factorization() {
+    let sieve = PrimeFactorSieve::new(100);
+    // 10! / (2! 3! 5!) = 2520.
+    assert_eq!(sieve.multinomial_factorization(&[2, 3, 5]).value(), 2_520);
+    assert_eq!(
+      sieve.multinomial_factorization(&[40, 60]),
+      sieve.binomial_factorization(100, 40)
+    );
+    assert_eq!(sieve.multinomial_factorization(&[]), Factorization::one());
+    assert_eq!(
+      sieve.try_multinomial_factorization(&[50, 51]),
+      Err(SieveError::OutOfRange { n: 101, max: 100 })
+    );
+  }
+}
@@ -0,0 +1,675 @@
+use std::{
+  cmp::Ordering,
+  collections::{BTreeMap, HashMap},
+  error::Error,
+  fmt::Display,
+  ops::{Div, Mul},
+  str::FromStr,
+};
+
+use crate::{
+  DivisorCount, DivisorSum, Divisors, Mobius, MultiplicativeFunction, PrimeFactorSieve, SieveError,
+  SieveInt, Totient, is_prime_miller_rabin,
+};
+
+/// The prime factorization of a positive integer, as (prime, exponent) pairs
+/// sorted by prime, with all exponents positive.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Factorization {
+  factors: Vec<(u64, u32)>,
+}
+
+impl Factorization {
+  /// The factorization of 1, which has no prime factors.
+  pub fn one() -> Self {
+    Self::default()
+  }
+
+  /// Returns the factorization with the given (prime, exponent) pairs, in any
+  /// order, combining repeated primes and skipping zero exponents. Returns an
+  /// error if a base is not prime, or if a combined exponent overflows a `u32`.
+  pub fn try_from_prime_powers(
+    prime_powers: impl IntoIterator<Item = (u64, u32)>,
+  ) -> Result<Self, ParseFactorizationError> {
+    let mut exponents = BTreeMap::<u64, u64>::new();
+    for (p, e) in prime_powers {
+      if e == 0 {
+        continue;
+      }
+      if !is_prime_miller_rabin(p) {
+        return Err(ParseFactorizationError::NotPrimePower { term: format!("{p}^{e}") });
+      }
+      *exponents.entry(p).or_insert(0) += u64::from(e);
+    }
+    let factors = exponents
+      .into_iter()
+      .map(|(p, e)| {
+        let e =
+          u32::try_from(e).map_err(|_| ParseFactorizationError::ExponentOverflow { prime: p })?;
+        Ok((p, e))
+      })
+      .collect::<Result<_, _>>()?;
+    Ok(Self { factors })
+  }
+
+  /// Returns the factorization with the given (prime, exponent) pairs, in any
+  /// order, skipping zero exponents. The bases must be distinct primes.
+  pub(crate) fn from_prime_powers_unchecked(
+    prime_powers: impl IntoIterator<Item = (u64, u32)>,
+  ) -> Self {
+    let mut factors = prime_powers
+      .into_iter()
+      .filter(|&(_, e)| e > 0)
+      .collect::<Vec<_>>();
+    factors.sort_unstable();
+    Self::from_sorted_unchecked(factors)
+  }
+
+  /// Returns the factorization with the given (prime, exponent) pairs, which
+  /// must be sorted by distinct primes with positive exponents.
+  pub(crate) fn from_sorted_unchecked(factors: Vec<(u64, u32)>) -> Self {
+    debug_assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+    debug_assert!(factors.iter().all(|&(_, e)| e > 0));
+    Self { factors }
+  }
+
+  /// Returns the (prime, exponent) pairs, sorted by prime.
+  pub fn prime_factors(&self) -> &[(u64, u32)] {
+    &self.factors
+  }
+
+  /// Returns whether this is the factorization of 1.
+  pub fn is_one(&self) -> bool {
+    self.factors.is_empty()
+  }
+
+  /// Returns the factored number. Panics if it does not fit in a `u64`.
+  pub fn value(&self) -> u64 {
+    self.try_value().unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_value(&self) -> Result<u64, SieveError> {
+    self.factors.iter().try_fold(1_u64, |value, &(p, e)| {
+      p.checked_pow(e)
+        .and_then(|pe| value.checked_mul(pe))
+        .ok_or(SieveError::Overflow)
+    })
+  }
+
+  /// Returns the factored number, or an error if it does not fit in a `u128`.
+  pub fn try_value_u128(&self) -> Result<u128, SieveError> {
+    self.factors.iter().try_fold(1_u128, |value, &(p, e)| {
+      (p as u128)
+        .checked_pow(e)
+        .and_then(|pe| value.checked_mul(pe))
+        .ok_or(SieveError::Overflow)
+    })
+  }
+
+  /// Merges the exponents of primes in either factorization with `merge`,
+  /// where a prime missing from one side has exponent 0, and drops primes
+  /// whose merged exponent is 0. Returns `None` if any merge does.
+  fn merge(&self, other: &Self, merge: impl Fn(u32, u32) -> Option<u32>) -> Option<Self> {
+    let (mut a, mut b) = (
+      self.factors.iter().peekable(),
+      other.factors.iter().peekable(),
+    );
+    let mut factors = Vec::with_capacity(self.factors.len() + other.factors.len());
+    loop {
+      let (p, e) = match (a.peek(), b.peek()) {
+        (None, None) => break,
+        (Some(&&(p, e)), None) => {
+          a.next();
+          (p, merge(e, 0)?)
+        }
+        (None, Some(&&(q, f))) => {
+          b.next();
+          (q, merge(0, f)?)
+        }
+        (Some(&&(p, e)), Some(&&(q, f))) => match p.cmp(&q) {
+          Ordering::Less => {
+            a.next();
+            (p, merge(e, 0)?)
+          }
+          Ordering::Greater => {
+            b.next();
+            (q, merge(0, f)?)
+          }
+          Ordering::Equal => {
+            a.next();
+            b.next();
+            (p, merge(e, f)?)
+          }
+        },
+      };
+      if e > 0 {
+        factors.push((p, e));
+      }
+    }
+    Some(Self { factors })
+  }
+
+  /// Returns self * other, or `None` if an exponent overflows a `u32`.
+  pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+    self.merge(other, u32::checked_add)
+  }
+
+  /// Returns self / other, or `None` if other does not divide self.
+  pub fn checked_div(&self, other: &Self) -> Option<Self> {
+    self.merge(other, u32::checked_sub)
+  }
+
+  /// Returns self^k. Panics if an exponent overflows a `u32`, see
+  /// `checked_pow`.
+  pub fn pow(&self, k: u32) -> Self {
+    self
+      .checked_pow(k)
+      .unwrap_or_else(|| panic!("({self})^{k} has an exponent which overflows a u32"))
+  }
+
+  /// Returns self^k, or `None` if an exponent overflows a `u32`.
+  pub fn checked_pow(&self, k: u32) -> Option<Self> {
+    if k == 0 {
+      return Some(Self::one());
+    }
+    let factors = self
+      .factors
+      .iter()
+      .map(|&(p, e)| Some((p, e.checked_mul(k)?)))
+      .collect::<Option<_>>()?;
+    Some(Self { factors })
+  }
+
+  pub fn gcd(&self, other: &Self) -> Self {
+    self.merge(other, |e, f| Some(e.min(f))).unwrap()
+  }
+
+  pub fn lcm(&self, other: &Self) -> Self {
+    self.merge(other, |e, f| Some(e.max(f))).unwrap()
+  }
+
+  /// Returns whether other divides self.
+  pub fn is_divisible_by(&self, other: &Self) -> bool {
+    let mut factors = self.factors.iter().peekable();
+    other.factors.iter().all(|&(q, f)| {
+      while factors.next_if(|&&(p, _)| p < q).is_some() {}
+      factors
+        .next_if(|&&(p, _)| p == q)
+        .is_some_and(|&(_, e)| e >= f)
+    })
+  }
+
+  /// Returns f of the factored number, for a multiplicative function f.
+  pub fn evaluate<F: MultiplicativeFunction>(&self, f: &F) -> F::Output {
+    self
+      .factors
+      .iter()
+      .fold(F::ONE, |value, &(p, e)| value * f.prime_power(p, e))
+  }
+
+  /// Returns an iterator over all divisors. Panics if the factored number does
+  /// not fit in a `u64`.
+  pub fn divisors(&self) -> Divisors<u64> {
+    // Also ensures there are few enough distinct primes for `Divisors`.
+    self.try_value().unwrap_or_else(|err| panic!("{err}"));
+    Divisors::new(self.factors.iter().copied())
+  }
+
+  /// Returns the number of divisors τ(n).
+  pub fn divisor_count(&self) -> u64 {
+    self.evaluate(&DivisorCount)
+  }
+
+  /// Returns σ_k(n), the sum of the k-th powers of the divisors, which must
+  /// fit in a `u128`.
+  pub fn divisor_sum(&self, k: u32) -> u128 {
+    self.evaluate(&DivisorSum(k))
+  }
+
+  /// Returns Euler's totient φ(n), which must fit in a `u64`.
+  pub fn totient(&self) -> u64 {
+    self.evaluate(&Totient)
+  }
+
+  /// Returns the Möbius function μ(n).
+  pub fn mobius(&self) -> i8 {
+    self.evaluate(&Mobius)
+  }
+}
+
+/// Panics if an exponent overflows a `u32`, see `checked_mul`.
+impl Mul for &Factorization {
+  type Output = Factorization;
+
+  fn mul(self, rhs: &Factorization) -> Factorization {
+    self
+      .checked_mul(rhs)
+      .unwrap_or_else(|| panic!("{self} · {rhs} has an exponent which overflows a u32"))
+  }
+}
+
+impl Mul for Factorization {
+  type Output = Factorization;
+
+  fn mul(self, rhs: Factorization) -> Factorization {
+    &self * &rhs
+  }
+}
+
+/// Panics if the divisor does not divide the dividend, see `checked_div`.
+impl Div for &Factorization {
+  type Output = Factorization;
+
+  fn div(self, rhs: &Factorization) -> Factorization {
+    self
+      .checked_div(rhs)
+      .unwrap_or_else(|| panic!("{self} is not divisible by {rhs}"))
+  }
+}
+
+impl Div for Factorization {
+  type Output = Factorization;
+
+  fn div(self, rhs: Factorization) -> Factorization {
+    &self / &rhs
+  }
+}
+
+/// Formats as e.g. `2^3 · 5`, or `1` if there are no prime factors.
+impl Display for Factorization {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if self.is_one() {
+      return write!(f, "1");
+    }
+    for (i, &(p, e)) in self.factors.iter().enumerate() {
+      if i > 0 {
+        write!(f, " · ")?;
+      }
+      match e {
+        1 => write!(f, "{p}")?,
+        _ => write!(f, "{p}^{e}")?,
+      }
+    }
+    Ok(())
+  }
+}
+
+/// An error returned when parsing or constructing a `Factorization`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseFactorizationError {
+  /// A term is not a positive power of a prime.
+  NotPrimePower { term: String },
+  /// The combined exponent of `prime` does not fit in a `u32`.
+  ExponentOverflow { prime: u64 },
+}
+
+impl Display for ParseFactorizationError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::NotPrimePower { term } => write!(f, "\"{term}\" is not a prime power"),
+      Self::ExponentOverflow { prime } => {
+        write!(f, "the exponent of {prime} does not fit in a u32")
+      }
+    }
+  }
+}
+
+impl Error for ParseFactorizationError {}
+
+/// Parses the `Display` format, also accepting `*` in place of `·` and prime
+/// powers in any order.
+impl FromStr for Factorization {
+  type Err = ParseFactorizationError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s.trim() == "1" {
+      return Ok(Self::one());
+    }
+    let prime_powers = s
+      .split(['·', '*'])
+      .map(|term| {
+        let error = || ParseFactorizationError::NotPrimePower { term: term.trim().to_owned() };
+        let (p, e) = term.split_once('^').unwrap_or((term, "1"));
+        let p = p.trim().parse::<u64>().map_err(|_| error())?;
+        let e = e.trim().parse::<u32>().map_err(|_| error())?;
+        if !is_prime_miller_rabin(p) || e == 0 {
+          return Err(error());
+        }
+        Ok((p, e))
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    Self::try_from_prime_powers(prime_powers)
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the prime factorization of n. Panics if n is 0 or out of range.
+  pub fn factorization(&self, n: Q) -> Factorization {
+    self
+      .try_factorization(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factorization(&self, n: Q) -> Result<Factorization, SieveError> {
+    Ok(Factorization {
+      factors: self
+        .try_prime_factors(n)?
+        .map(|(p, e)| (p.as_u64(), e))
+        .collect(),
+    })
+  }
+
+  /// Returns the factorization of the gcd of all of `ns`. Panics if there are
+  /// no inputs (as gcd() = 0), or if any input is 0 or out of range.
+  pub fn gcd_factorization(&self, ns: impl IntoIterator<Item = Q>) -> Factorization {
+    self
+      .try_gcd_factorization(ns)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  /// Only the first input is factored. The exponents of its primes are then
+  /// lowered by dividing each remaining input, stopping early once the gcd is
+  /// 1 (though every input is still checked).
+  pub fn try_gcd_factorization(
+    &self,
+    ns: impl IntoIterator<Item = Q>,
+  ) -> Result<Factorization, SieveError> {
+    let mut ns = ns.into_iter();
+    let mut gcd = self.try_factorization(ns.next().ok_or(SieveError::Zero)?)?;
+    for n in ns {
+      self.check_factorable(n)?;
+      if gcd.is_one() {
+        continue;
+      }
+      let n = n.as_u64();
+      for (p, e) in &mut gcd.factors {
+        let mut pe = 1;
+        let mut count = 0;
+        while count < *e && n.is_multiple_of(pe * *p) {
+          pe *= *p;
+          count += 1;
+        }
+        *e = count;
+      }
+      gcd.factors.retain(|&(_, e)| e > 0);
+    }
+    Ok(gcd)
+  }
+
+  /// Returns the gcd of all of `ns`. Panics if there are no inputs, or if any
+  /// input is 0 or out of range.
+  pub fn gcd_of(&self, ns: impl IntoIterator<Item = Q>) -> Q {
+    Q::from_u64(self.gcd_factorization(ns).value())
+  }
+
+  /// Returns the factorization of the lcm of all of `ns`, which is 1 if there
+  /// are no inputs. Panics if any input is 0 or out of range.
+  pub fn lcm_factorization(&self, ns: impl IntoIterator<Item = Q>) -> Factorization {
+    self
+      .try_lcm_factorization(ns)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_lcm_factorization(
+    &self,
+    ns: impl IntoIterator<Item = Q>,
+  ) -> Result<Factorization, SieveError> {
+    let mut exponents = HashMap::new();
+    for n in ns {
+      for (p, e) in self.try_prime_factors(n)? {
+        let max = exponents.entry(p.as_u64()).or_insert(0);
+        *max = e.max(*max);
+      }
+    }
+    Ok(Factorization::from_prime_powers_unchecked(exponents))
+  }
+
+  /// Returns the factorization of the product of all of `ns`, which is 1 if
+  /// there are no inputs. The product itself may be far larger than a `u64`.
+  /// Panics if any input is 0 or out of range.
+  pub fn product_factorization(&self, ns: impl IntoIterator<Item = Q>) -> Factorization {
+    self
+      .try_product_factorization(ns)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_product_factorization(
+    &self,
+    ns: impl IntoIterator<Item = Q>,
+  ) -> Result<Factorization, SieveError> {
+    let mut exponents = HashMap::new();
+    for n in ns {
+      for (p, e) in self.try_prime_factors(n)? {
+        let sum: &mut u32 = exponents.entry(p.as_u64()).or_insert(0);
+        *sum = sum.checked_add(e).ok_or(SieveError::Overflow)?;
+      }
+    }
+    Ok(Factorization::from_prime_powers_unchecked(exponents))
+  }
+
+  /// Returns the lcm of all of `ns`. Panics if any input is 0 or out of range,
+  /// or if the lcm does not fit in a `u64`.
+  pub fn lcm_of(&self, ns: impl IntoIterator<Item = Q>) -> u64 {
+    self.lcm_factorization(ns).value()
+  }
+
+  pub fn try_lcm_of(&self, ns: impl IntoIterator<Item = Q>) -> Result<u64, SieveError> {
+    self.try_lcm_factorization(ns)?.try_value()
+  }
+
+  pub fn try_lcm_of_u128(&self, ns: impl IntoIterator<Item = Q>) -> Result<u128, SieveError> {
+    self.try_lcm_factorization(ns)?.try_value_u128()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, SieveError};
+
+  use super::{Factorization, ParseFactorizationError};
+
+  #[test]
+  fn test_arithmetic() {
+    let sieve = PrimeFactorSieve::new(2_000);
+    for a in (1..=2_000).step_by(7) {
+      let fa = sieve.factorization(a);
+      assert_eq!(fa.value(), a as u64);
+      for b in (1..=2_000).step_by(37) {
+        let fb = sieve.factorization(b);
+        let (a, b) = (a as u64, b as u64);
+        assert_eq!((&fa * &fb).value(), a * b);
+        assert_eq!(fa.gcd(&fb).value(), num_gcd(a, b));
+        assert_eq!(fa.lcm(&fb).value(), a / num_gcd(a, b) * b);
+        assert_eq!(fa.is_divisible_by(&fb), a % b == 0);
+        assert_eq!(
+          fa.checked_div(&fb).map(|q| q.value()),
+          (a % b == 0).then(|| a / b)
+        );
+      }
+    }
+
+    let twelve = sieve.factorization(12);
+    assert_eq!(twelve.pow(3).value(), 1_728);
+    assert_eq!(twelve.pow(0), Factorization::one());
+    assert_eq!(
+      twelve.clone() / sieve.factorization(4),
+      sieve.factorization(3)
+    );
+    assert_eq!(twelve.clone() * Factorization::one(), twelve);
+    assert_eq!(Factorization::one().value(), 1);
+  }
+
+  fn num_gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { num_gcd(b, a % b) }
+  }
+
+  #[test]
+  #[should_panic(expected = "2 is not divisible by 3")]
+  fn test_div_not_divisible() {
+    let sieve = PrimeFactorSieve::new(10);
+    let _ = sieve.factorization(2) / sieve.factorization(3);
+  }
+
+  #[test]
+  fn test_values() {
+    let big = Factorization::try_from_prime_powers([(2, 40), (3, 20)]).unwrap();
+    assert_eq!(big.try_value(), Err(SieveError::Overflow));
+    assert_eq!(big.try_value_u128(), Ok((1 << 40) * 3_u128.pow(20)));
+    assert_eq!(
+      Factorization::try_from_prime_powers([(2, 130)])
+        .unwrap()
+        .try_value_u128(),
+      Err(SieveError::Overflow)
+    );
+    assert_eq!(
+      Factorization::try_from_prime_powers([(5, 1), (2, 1), (2, 2), (3, 0)]),
+      Factorization::try_from_prime_powers([(2, 3), (5, 1)])
+    );
+  }
+
+  #[test]
+  fn test_from_prime_powers() {
+    assert_eq!(
+      Factorization::try_from_prime_powers([(4, 1)]),
+      Err(ParseFactorizationError::NotPrimePower { term: "4^1".to_owned() })
+    );
+    assert_eq!(
+      Factorization::try_from_prime_powers([(1, 3)]),
+      Err(ParseFactorizationError::NotPrimePower { term: "1^3".to_owned() })
+    );
+    assert_eq!(
+      Factorization::try_from_prime_powers([(4, 0), (1, 0)]),
+      Ok(Factorization::one())
+    );
+    assert_eq!(
+      Factorization::try_from_prime_powers([(3, u32::MAX), (3, 1)]),
+      Err(ParseFactorizationError::ExponentOverflow { prime: 3 })
+    );
+    assert_eq!(
+      "3^4294967295 * 3".parse::<Factorization>(),
+      Err(ParseFactorizationError::ExponentOverflow { prime: 3 })
+    );
+    assert_eq!(
+      ParseFactorizationError::ExponentOverflow { prime: 3 }.to_string(),
+      "the exponent of 3 does not fit in a u32"
+    );
+  }
+
+  #[test]
+  fn test_exponent_overflow() {
+    let big = Factorization::try_from_prime_powers([(2, u32::MAX), (3, 1)]).unwrap();
+    let two = Factorization::try_from_prime_powers([(2, 1)]).unwrap();
+    assert_eq!(big.checked_mul(&two), None);
+    assert_eq!(big.checked_pow(2), None);
+    assert_eq!(
+      two.checked_pow(u32::MAX).unwrap().prime_factors(),
+      &[(2, u32::MAX)]
+    );
+    assert_eq!(big.checked_pow(0), Some(Factorization::one()));
+  }
+
+  #[test]
+  #[should_panic(expected = "overflows a u32")]
+  fn test_mul_overflow() {
+    let big = Factorization::try_from_prime_powers([(2, u32::MAX)]).unwrap();
+    let _ = &big * &big;
+  }
+
+  #[test]
+  fn test_functions() {
+    let sieve = PrimeFactorSieve::new(5_000);
+    for n in 1..=5_000 {
+      let f = sieve.factorization(n);
+      assert_eq!(
+        f.divisors().sorted().collect_vec(),
+        sieve.factors_sorted(n).map(u64::from).collect_vec()
+      );
+      assert_eq!(f.divisor_count(), sieve.factors_count(n) as u64);
+      assert_eq!(f.divisor_sum(1), sieve.divisor_sum::<u128>(n, 1));
+      assert_eq!(f.totient(), sieve.totient(n) as u64);
+      assert_eq!(f.mobius(), sieve.mobius(n));
+    }
+  }
+
+  #[test]
+  fn test_gcd_lcm() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    for a in (1..=1_000_u32).step_by(11) {
+      for b in (1..=1_000_u32).step_by(29) {
+        for c in [1, 2, 12, 360, 997, 1_000] {
+          let (a64, b64, c64) = (a as u64, b as u64, c as u64);
+          let gcd = num_gcd(num_gcd(a64, b64), c64);
+          let lcm = a64 / num_gcd(a64, b64) * b64;
+          let lcm = lcm / num_gcd(lcm, c64) * c64;
+          assert_eq!(sieve.gcd_of([a, b, c]), gcd as u32);
+          assert_eq!(sieve.lcm_of([a, b, c]), lcm);
+          assert_eq!(
+            sieve.gcd_factorization([a, b, c]),
+            sieve.factorization(gcd as u32)
+          );
+        }
+      }
+    }
+
+    assert_eq!(sieve.gcd_of([360]), 360);
+    assert_eq!(sieve.lcm_factorization([]), Factorization::one());
+    assert_eq!(sieve.try_gcd_factorization([]), Err(SieveError::Zero));
+    assert_eq!(
+      sieve.try_gcd_factorization([6, 35, 1_001]),
+      Err(SieveError::OutOfRange { n: 1_001, max: 1_000 })
+    );
+    assert_eq!(sieve.try_lcm_of([6, 0]), Err(SieveError::Zero));
+  }
+
+  #[test]
+  fn test_product_factorization() {
+    let sieve = PrimeFactorSieve::new(100);
+    assert_eq!(sieve.product_factorization([]), Factorization::one());
+    assert_eq!(
+      sieve.product_factorization([12, 18, 100]).to_string(),
+      "2^5 · 3^3 · 5^2"
+    );
+    assert_eq!(
+      sieve.try_product_factorization([4, 0]),
+      Err(SieveError::Zero)
+    );
+  }
+
+  #[test]
+  fn test_lcm_range() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.lcm_of(1..=20), 232_792_560);
+    assert_eq!(sieve.try_lcm_of(1..=50), Err(SieveError::Overflow));
+    assert_eq!(
+      sieve.try_lcm_of_u128(1..=50),
+      Ok(3_099_044_504_245_996_706_400)
+    );
+    assert_eq!(
+      sieve.lcm_factorization(1..=1_000).prime_factors().len(),
+      168
+    );
+  }
+
+  #[test]
+  fn test_display_and_parse() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.factorization(40).to_string(), "2^3 · 5");
+    assert_eq!(sieve.factorization(1).to_string(), "1");
+    assert_eq!(sieve.factorization(997).to_string(), "997");
+    for n in 1..=1_000 {
+      let f = sieve.factorization(n);
+      assert_eq!(f.to_string().parse::<Factorization>(), Ok(f));
+    }
+
+    assert_eq!("5 * 2^3".parse(), Ok(sieve.factorization(40)));
+    assert_eq!(
+      "2^3 · 6".parse::<Factorization>(),
+      Err(ParseFactorizationError::NotPrimePower { term: "6".to_owned() })
+    );
+    assert!("2^0".parse::<Factorization>().is_err());
+    assert!("2^x".parse::<Factorization>().is_err());
+    assert!("".parse::<Factorization>().is_err());
+  }
+}
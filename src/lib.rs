@@ -1,3 +1,40 @@
+mod dirichlet;
+mod divisor_sum;
+mod divisors;
+mod error;
+mod factorial;
+mod factorization;
+mod mobius;
+mod multiplicative;
+mod pollard_rho;
+mod primality;
+mod prime_bit_sieve;
+mod prime_count;
 mod prime_factor_sieve;
+mod prime_index;
+mod prime_range;
+mod prime_sieve;
+mod prime_sum;
+mod range_factorization;
+mod segmented_sieve;
+mod sieve_builder;
+mod sieve_int;
+mod spf_table;
 
+pub use dirichlet::*;
+pub use divisors::*;
+pub use error::*;
+pub use factorial::*;
+pub use factorization::*;
+pub use multiplicative::*;
+pub use primality::*;
+pub use prime_bit_sieve::*;
 pub use prime_factor_sieve::*;
+pub use prime_range::*;
+pub use prime_sieve::*;
+pub use prime_sum::*;
+pub use range_factorization::*;
+pub use segmented_sieve::*;
+pub use sieve_builder::*;
+pub use sieve_int::*;
+pub use spf_table::SpfLayout;
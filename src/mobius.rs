@@ -0,0 +1,201 @@
+use std::borrow::Cow;
+
+use crate::{PrimeFactorSieve, SieveError, SieveInt};
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the Möbius function μ(n). Panics if n is 0 or out of range.
+  pub fn mobius(&self, n: Q) -> i8 {
+    self.try_mobius(n).unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_mobius(&self, n: Q) -> Result<i8, SieveError> {
+    let mut prime_factors = self.try_prime_factors(n)?;
+    if let Some(mobius) = self.mobius_table() {
+      return Ok(mobius[n.as_usize()]);
+    }
+
+    Ok(
+      prime_factors
+        .try_fold(1, |mu, (_, count)| (count == 1).then_some(-mu))
+        .unwrap_or(0),
+    )
+  }
+
+  /// Computes μ(n) for all n <= limit, which must be within the bound.
+  pub(crate) fn compute_mobius(&self, limit: usize) -> Vec<i8> {
+    let mut mobius = vec![0; limit + 1];
+    if limit >= 1 {
+      mobius[1] = 1;
+    }
+    for n in 2..=limit {
+      let p = self.spf(n);
+      let m = n / p;
+      mobius[n] = if self.spf(m) == p { 0 } else { -mobius[m] };
+    }
+    mobius
+  }
+
+  /// Returns the Mertens function M(x) = μ(1) + μ(2) + ... + μ(x), for any x.
+  ///
+  /// Values up to min(bound, x^(2/3)) are summed directly from the sieve, and
+  /// M(v) for the remaining distinct values v = x / d is computed with the
+  /// identity sum_{k=1..v} M(v / k) = 1. This takes O(x^(2/3)) time if the
+  /// sieve covers x^(2/3), and at most O(x^(3/4)) time and O(sqrt(x)) memory
+  /// otherwise.
+  pub fn mertens(&self, x: u64) -> i64 {
+    let two_thirds = (x as f64).powf(2. / 3.) as u64;
+    let r = x.isqrt() as usize;
+    let limit = self
+      .bound()
+      .as_usize()
+      .min(two_thirds.max(r as u64) as usize);
+
+    let mobius = match self.mobius_table() {
+      Some(mobius) => Cow::Borrowed(&mobius[..=limit]),
+      None => Cow::Owned(self.compute_mobius(limit)),
+    };
+    let mut small = mobius
+      .iter()
+      .scan(0, |m, &mu| {
+        *m += mu as i64;
+        Some(*m)
+      })
+      .collect::<Vec<_>>();
+    if x <= limit as u64 {
+      return small[x as usize];
+    }
+
+    // If the sieve does not reach sqrt(x), extend small[v] = M(v) up to it,
+    // so that every v = x / d not in small has d <= sqrt(x).
+    for v in limit + 1..=r {
+      let m = mertens_recurrence(v as u64, |q| small[q as usize]);
+      small.push(m);
+    }
+    let limit = small.len() as u64 - 1;
+    if x <= limit {
+      return small[x as usize];
+    }
+
+    // large[d] = M(x / d), for all d with x / d > limit.
+    let max_d = (x / (limit + 1)) as usize;
+    let mut large = vec![0; max_d + 1];
+    for d in (1..=max_d).rev() {
+      let v = x / d as u64;
+      // Any k with v / k = q > limit can be replaced by v / q, the largest such k.
+      large[d] = mertens_recurrence(v, |q| {
+        if q <= limit {
+          small[q as usize]
+        } else {
+          large[d * (v / q) as usize]
+        }
+      });
+    }
+
+    large[1]
+  }
+}
+
+/// Returns M(v) = 1 - sum_{k=2..v} M(v / k), given `mertens(q)` = M(q) for
+/// every q = v / k with k >= 2.
+fn mertens_recurrence(v: u64, mertens: impl Fn(u64) -> i64) -> i64 {
+  let mut m = 1;
+  let mut k = 2;
+  while k <= v {
+    // All k' in [k, k_hi] have the same v / k'.
+    let q = v / k;
+    let k_hi = v / q;
+    m -= mertens(q) * (k_hi - k + 1) as i64;
+    k = k_hi + 1;
+  }
+  m
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeFactorSieveBuilder, SieveError, SpfLayout};
+
+  #[test]
+  fn test_mobius() {
+    let sieve = PrimeFactorSieve::new(30);
+    assert_eq!(
+      (1..=30).map(|n| sieve.mobius(n)).collect_vec(),
+      vec![
+        1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0, -1, 1, 1, 0, -1, 0, -1, 0, 1, 1, -1, 0, 0, 1, 0,
+        0, -1, -1
+      ]
+    );
+    assert_eq!(sieve.try_mobius(0), Err(SieveError::Zero));
+    assert_eq!(
+      sieve.try_mobius(31),
+      Err(SieveError::OutOfRange { n: 31, max: 30 })
+    );
+  }
+
+  #[test]
+  fn test_mobius_table() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    assert_eq!(sieve.mobius_table(), None);
+    for layout in [SpfLayout::Full, SpfLayout::OddOnly, SpfLayout::Wheel30] {
+      let stored: PrimeFactorSieve = PrimeFactorSieveBuilder::new(10_000)
+        .layout(layout)
+        .store_mobius(true)
+        .build();
+      let table = stored.mobius_table().unwrap();
+      assert_eq!(table.len(), 10_001);
+      assert_eq!(table[0], 0);
+      for n in 1..=10_000 {
+        assert_eq!(table[n as usize], sieve.mobius(n));
+        assert_eq!(stored.mobius(n), sieve.mobius(n));
+      }
+      assert_eq!(stored.try_mobius(0), Err(SieveError::Zero));
+    }
+  }
+
+  #[test]
+  fn test_mertens_small() {
+    let sieve = PrimeFactorSieve::new(100_000);
+    let small = PrimeFactorSieve::new(50);
+    let mut m = 0;
+    for x in 1..=100_000 {
+      m += sieve.mobius(x) as i64;
+      if x <= 1_000 || x % 97 == 0 {
+        assert_eq!(sieve.mertens(x as u64), m);
+        assert_eq!(small.mertens(x as u64), m);
+      }
+    }
+    assert_eq!(sieve.mertens(0), 0);
+
+    let expected = (0..3_000)
+      .scan(0, |m, x| {
+        if x > 0 {
+          *m += sieve.mobius(x) as i64;
+        }
+        Some(*m)
+      })
+      .collect_vec();
+    for bound in 0..20 {
+      let tiny = PrimeFactorSieve::new(bound);
+      for (x, &m) in expected.iter().enumerate() {
+        assert_eq!(tiny.mertens(x as u64), m, "bound {bound}, x {x}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_mertens_large() {
+    let sieve: PrimeFactorSieve = PrimeFactorSieveBuilder::new(1_000_000)
+      .store_mobius(true)
+      .build();
+    assert_eq!(sieve.mertens(1_000_000), 212);
+    assert_eq!(sieve.mertens(10_000_000), 1_037);
+    assert_eq!(sieve.mertens(100_000_000), 1_928);
+    assert_eq!(sieve.mertens(1_000_000_000), -222);
+    assert_eq!(PrimeFactorSieve::new(10_000).mertens(1_000_000_000), -222);
+    // The sieve need not reach sqrt(x).
+    assert_eq!(PrimeFactorSieve::new(10).mertens(1_000_000_000), -222);
+    assert_eq!(PrimeFactorSieve::new(1).mertens(10_000_000), 1_037);
+    assert_eq!(PrimeFactorSieve::new(0).mertens(1), 1);
+  }
+}
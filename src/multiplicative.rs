@@ -0,0 +1,220 @@
+use std::ops::Mul;
+
+use crate::{PrimeFactorSieve, SieveError, SieveInt};
+
+/// An arithmetic function f with f(mn) = f(m) f(n) for all coprime m and n,
+/// which is therefore determined by its values at prime powers.
+pub trait MultiplicativeFunction {
+  type Output: Copy + Default + Mul<Output = Self::Output>;
+
+  /// The value of f(1), which is the multiplicative identity.
+  const ONE: Self::Output;
+
+  /// Returns f(p^k), for prime p and k >= 1.
+  fn prime_power(&self, p: u64, k: u32) -> Self::Output;
+}
+
+/// Euler's totient φ(n), the number of integers in [1, n] coprime to n.
+pub struct Totient;
+
+impl MultiplicativeFunction for Totient {
+  type Output = u64;
+  const ONE: u64 = 1;
+
+  fn prime_power(&self, p: u64, k: u32) -> u64 {
+    p.pow(k - 1) * (p - 1)
+  }
+}
+
+/// The Möbius function μ(n), which is 0 if n is not squarefree, and otherwise
+/// (-1)^(number of prime factors of n).
+pub struct Mobius;
+
+impl MultiplicativeFunction for Mobius {
+  type Output = i8;
+  const ONE: i8 = 1;
+
+  fn prime_power(&self, _p: u64, k: u32) -> i8 {
+    if k == 1 { -1 } else { 0 }
+  }
+}
+
+/// The divisor function σ_k(n), the sum of the k-th powers of the divisors of
+/// n.
+pub struct DivisorSum(pub u32);
+
+impl MultiplicativeFunction for DivisorSum {
+  type Output = u128;
+  const ONE: u128 = 1;
+
+  fn prime_power(&self, p: u64, k: u32) -> u128 {
+    let pk = (p as u128).pow(self.0);
+    (0..k).fold(1, |sum, _| sum * pk + 1)
+  }
+}
+
+/// The number of divisors τ(n), which is σ_0(n).
+pub struct DivisorCount;
+
+impl MultiplicativeFunction for DivisorCount {
+  type Output = u64;
+  const ONE: u64 = 1;
+
+  fn prime_power(&self, _p: u64, k: u32) -> u64 {
+    k as u64 + 1
+  }
+}
+
+/// The Liouville function λ(n) = (-1)^(number of prime factors of n, counted
+/// with multiplicity).
+pub struct Liouville;
+
+impl MultiplicativeFunction for Liouville {
+  type Output = i8;
+  const ONE: i8 = 1;
+
+  fn prime_power(&self, _p: u64, k: u32) -> i8 {
+    if k.is_multiple_of(2) { 1 } else { -1 }
+  }
+}
+
+/// The radical rad(n), the product of the distinct primes dividing n.
+pub struct Radical;
+
+impl MultiplicativeFunction for Radical {
+  type Output = u64;
+  const ONE: u64 = 1;
+
+  fn prime_power(&self, p: u64, _k: u32) -> u64 {
+    p
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns f(n), computed from the prime factorization of n. Panics if n is 0
+  /// or out of range.
+  pub fn evaluate<F: MultiplicativeFunction>(&self, f: &F, n: Q) -> F::Output {
+    self
+      .try_evaluate(f, n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_evaluate<F: MultiplicativeFunction>(
+    &self,
+    f: &F,
+    n: Q,
+  ) -> Result<F::Output, SieveError> {
+    Ok(
+      self
+        .try_prime_factors(n)?
+        .fold(F::ONE, |value, (p, k)| value * f.prime_power(p.as_u64(), k)),
+    )
+  }
+
+  /// Returns f(n) for all n <= bound, with f(0) set to the default value of the
+  /// output. Each entry is computed from an earlier one with a single
+  /// multiplication, so this runs in linear time.
+  pub fn multiplicative_table<F: MultiplicativeFunction>(&self, f: &F) -> Vec<F::Output> {
+    let bound = self.bound().as_usize();
+    let mut values = vec![F::Output::default(); bound + 1];
+    if bound >= 1 {
+      values[1] = F::ONE;
+    }
+    for n in 2..=bound {
+      let (p, k, m) = self.split_prime_power(n);
+      values[n] = f.prime_power(p as u64, k) * values[m];
+    }
+    values
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeFactorSieveBuilder, SieveError, SpfLayout};
+
+  use super::{
+    DivisorCount, DivisorSum, Liouville, Mobius, MultiplicativeFunction, Radical, Totient,
+  };
+
+  /// The number of squares dividing n, which is multiplicative.
+  struct SquareDivisors;
+
+  impl MultiplicativeFunction for SquareDivisors {
+    type Output = u32;
+    const ONE: u32 = 1;
+
+    fn prime_power(&self, _p: u64, k: u32) -> u32 {
+      k / 2 + 1
+    }
+  }
+
+  #[test]
+  fn test_builtins() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    for n in 1..=1_000 {
+      assert_eq!(sieve.evaluate(&Totient, n), sieve.totient(n) as u64);
+      assert_eq!(sieve.evaluate(&Mobius, n), sieve.mobius(n));
+      assert_eq!(
+        sieve.evaluate(&DivisorSum(2), n),
+        sieve.divisor_sum::<u128>(n, 2)
+      );
+      assert_eq!(
+        sieve.evaluate(&DivisorCount, n),
+        sieve.factors_count(n) as u64
+      );
+
+      let prime_factors = sieve.prime_factors(n).collect_vec();
+      let omega = prime_factors.iter().map(|&(_, k)| k).sum::<u32>();
+      assert_eq!(
+        sieve.evaluate(&Liouville, n),
+        if omega.is_multiple_of(2) { 1 } else { -1 }
+      );
+      assert_eq!(
+        sieve.evaluate(&Radical, n),
+        prime_factors
+          .iter()
+          .map(|&(p, _)| p as u64)
+          .product::<u64>()
+      );
+    }
+    assert_eq!(sieve.try_evaluate(&Totient, 0), Err(SieveError::Zero));
+  }
+
+  #[test]
+  fn test_custom() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    for n in 1..=1_000 {
+      let expected = sieve.factors(n).filter(|&d| d.isqrt().pow(2) == d).count() as u32;
+      assert_eq!(sieve.evaluate(&SquareDivisors, n), expected);
+    }
+  }
+
+  #[test]
+  fn test_tables() {
+    for layout in [SpfLayout::Full, SpfLayout::Wheel30] {
+      let sieve: PrimeFactorSieve = PrimeFactorSieveBuilder::new(10_000).layout(layout).build();
+      let totient = sieve.multiplicative_table(&Totient);
+      let mobius = sieve.multiplicative_table(&Mobius);
+      let sigma = sieve.multiplicative_table(&DivisorSum(1));
+      let tau = sieve.multiplicative_table(&DivisorCount);
+      let liouville = sieve.multiplicative_table(&Liouville);
+      let radical = sieve.multiplicative_table(&Radical);
+      let squares = sieve.multiplicative_table(&SquareDivisors);
+      assert_eq!(totient.len(), 10_001);
+      assert_eq!((totient[0], mobius[0], radical[0]), (0, 0, 0));
+
+      for n in 1..=10_000 {
+        let i = n as usize;
+        assert_eq!(totient[i], sieve.evaluate(&Totient, n));
+        assert_eq!(mobius[i], sieve.evaluate(&Mobius, n));
+        assert_eq!(sigma[i], sieve.evaluate(&DivisorSum(1), n));
+        assert_eq!(tau[i], sieve.evaluate(&DivisorCount, n));
+        assert_eq!(liouville[i], sieve.evaluate(&Liouville, n));
+        assert_eq!(radical[i], sieve.evaluate(&Radical, n));
+        assert_eq!(squares[i], sieve.evaluate(&SquareDivisors, n));
+      }
+    }
+  }
+}
@@ -0,0 +1,259 @@
+use crate::{
+  PrimeFactorSieve, SieveError, SieveInt,
+  primality::{ModInt, is_probable_prime},
+};
+
+/// Primes up to this bound are trial-divided out before falling back to
+/// Pollard's rho.
+const TRIAL_DIVISION_BOUND: u64 = 1 << 12;
+
+/// Number of steps of Brent's cycle finding taken between gcd computations.
+const BRENT_BATCH_SIZE: u64 = 128;
+
+fn abs_diff<T: ModInt>(a: T, b: T) -> T {
+  if a > b { a - b } else { b - a }
+}
+
+/// Returns a nontrivial factor of n, which must be odd and composite, using
+/// Pollard's rho algorithm with Brent's cycle detection.
+fn pollard_brent<T: ModInt>(n: T) -> T {
+  for c in 1.. {
+    let c = T::from_u64(c);
+    let f = |x: T| T::add_mod(T::mul_mod(x, x, n), c, n);
+
+    let (mut x, mut y, mut ys) = (T::ZERO, T::from_u64(2), T::ZERO);
+    let mut q = T::ONE;
+    let mut g = T::ONE;
+    let mut r = 1;
+    while g == T::ONE {
+      x = y;
+      for _ in 0..r {
+        y = f(y);
+      }
+
+      let mut k = 0;
+      while k < r && g == T::ONE {
+        ys = y;
+        for _ in 0..BRENT_BATCH_SIZE.min(r - k) {
+          y = f(y);
+          q = T::mul_mod(q, abs_diff(x, y), n);
+        }
+        g = T::gcd(q, n);
+        k += BRENT_BATCH_SIZE;
+      }
+      r *= 2;
+    }
+
+    if g == n {
+      // The batch overshot, so retrace it one step at a time.
+      loop {
+        ys = f(ys);
+        g = T::gcd(abs_diff(x, ys), n);
+        if g != T::ONE {
+          break;
+        }
+      }
+    }
+
+    // If g is still n, x and y collided mod n itself, so retry with another
+    // polynomial.
+    if g != n {
+      return g;
+    }
+  }
+
+  unreachable!()
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns an iterator over prime factors (p, multiplicity) of any nonzero
+  /// u64, in ascending order. Small primes are trial-divided with the primes of
+  /// the sieve, and the remaining cofactor is factored with the table if within
+  /// the bound, and otherwise with Miller–Rabin and Pollard–Brent rho. Panics
+  /// if n is 0.
+  pub fn factorize_u64(&self, n: u64) -> impl Iterator<Item = (u64, u32)> {
+    self
+      .try_factorize_u64(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factorize_u64(&self, n: u64) -> Result<impl Iterator<Item = (u64, u32)>, SieveError> {
+    self.factorize(n).map(Vec::into_iter)
+  }
+
+  /// Like `factorize_u64`, but for any nonzero u128. Prime factors >= 2^64 are
+  /// only probable primes, see `is_probable_prime_u128`. Panics if n is 0.
+  pub fn factorize_u128(&self, n: u128) -> impl Iterator<Item = (u128, u32)> {
+    self
+      .try_factorize_u128(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factorize_u128(
+    &self,
+    n: u128,
+  ) -> Result<impl Iterator<Item = (u128, u32)>, SieveError> {
+    self.factorize(n).map(Vec::into_iter)
+  }
+
+  fn factorize<T: ModInt>(&self, mut n: T) -> Result<Vec<(T, u32)>, SieveError> {
+    if n == T::ZERO {
+      return Err(SieveError::Zero);
+    }
+    let bound = self.bound().as_u64() as u128;
+
+    let mut factors = Vec::new();
+    for p in self
+      .primes()
+      .map(Q::as_u64)
+      .take_while(|&p| p <= TRIAL_DIVISION_BOUND)
+    {
+      if n.as_u128() <= bound || (p as u128 * p as u128) > n.as_u128() {
+        break;
+      }
+
+      let p = T::from_u64(p);
+      while n % p == T::ZERO {
+        n = n / p;
+        factors.push((p, 1));
+      }
+    }
+    self.factorize_cofactor(n, &mut factors);
+
+    factors.sort_unstable();
+    factors.dedup_by(|(p, count), (prev_p, prev_count)| {
+      let same = p == prev_p;
+      if same {
+        *prev_count += *count;
+      }
+      same
+    });
+    Ok(factors)
+  }
+
+  fn factorize_cofactor<T: ModInt>(&self, n: T, factors: &mut Vec<(T, u32)>) {
+    if n == T::ONE {
+      return;
+    }
+    if n.as_u128() <= self.bound().as_u64() as u128 {
+      let n = Q::from_u64(n.as_u128() as u64);
+      factors.extend(
+        self
+          .prime_factors(n)
+          .map(|(p, count)| (T::from_u64(p.as_u64()), count)),
+      );
+      return;
+    }
+    if n & T::ONE == T::ZERO {
+      factors.push((T::from_u64(2), 1));
+      return self.factorize_cofactor(n >> 1, factors);
+    }
+    if is_probable_prime(n) {
+      factors.push((n, 1));
+      return;
+    }
+
+    let d = pollard_brent(n);
+    self.factorize_cofactor(d, factors);
+    self.factorize_cofactor(n / d, factors);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, SieveError};
+
+  #[test]
+  fn test_matches_prime_factors() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    let small = PrimeFactorSieve::new(10);
+    for n in 1..=10_000 {
+      let expected = sieve
+        .prime_factors(n)
+        .map(|(p, count)| (p as u64, count))
+        .collect_vec();
+      assert_eq!(sieve.factorize_u64(n as u64).collect_vec(), expected);
+      assert_eq!(small.factorize_u64(n as u64).collect_vec(), expected);
+    }
+  }
+
+  #[test]
+  fn test_large() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(
+      sieve.factorize_u64(600_851_475_143).collect_vec(),
+      vec![(71, 1), (839, 1), (1471, 1), (6857, 1)]
+    );
+    assert_eq!(
+      sieve.factorize_u64(u64::MAX).collect_vec(),
+      vec![
+        (3, 1),
+        (5, 1),
+        (17, 1),
+        (257, 1),
+        (641, 1),
+        (65537, 1),
+        (6_700_417, 1)
+      ]
+    );
+    assert_eq!(
+      sieve
+        .factorize_u64(1_000_000_007 * 1_000_000_009)
+        .collect_vec(),
+      vec![(1_000_000_007, 1), (1_000_000_009, 1)]
+    );
+    assert_eq!(sieve.factorize_u64(1 << 63).collect_vec(), vec![(2, 63)]);
+    assert_eq!(
+      sieve
+        .factorize_u64(18_446_744_073_709_551_557)
+        .collect_vec(),
+      vec![(18_446_744_073_709_551_557, 1)]
+    );
+    assert_eq!(
+      sieve
+        .factorize_u64(4_294_967_291 * 4_294_967_291)
+        .collect_vec(),
+      vec![(4_294_967_291, 2)]
+    );
+  }
+
+  #[test]
+  fn test_zero() {
+    let sieve = PrimeFactorSieve::new(10);
+    assert_eq!(sieve.try_factorize_u64(0).err(), Some(SieveError::Zero));
+    assert_eq!(sieve.try_factorize_u128(0).err(), Some(SieveError::Zero));
+    assert_eq!(sieve.try_factorize_u64(1).unwrap().count(), 0);
+  }
+
+  #[test]
+  fn test_u128() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    let mersenne_61 = (1 << 61) - 1;
+    assert_eq!(
+      sieve
+        .factorize_u128(9 * 1_000_003 * 1_000_000_007 * mersenne_61)
+        .collect_vec(),
+      vec![(3, 2), (1_000_003, 1), (1_000_000_007, 1), (mersenne_61, 1)]
+    );
+    assert_eq!(
+      sieve.factorize_u128((1 << 89) - 1).collect_vec(),
+      vec![((1 << 89) - 1, 1)]
+    );
+    assert_eq!(
+      sieve.factorize_u128(u128::MAX).collect_vec(),
+      vec![
+        (3, 1),
+        (5, 1),
+        (17, 1),
+        (257, 1),
+        (641, 1),
+        (65537, 1),
+        (274_177, 1),
+        (6_700_417, 1),
+        (67_280_421_310_721, 1)
+      ]
+    );
+  }
+}
@@ -0,0 +1,263 @@
+use std::ops::{Add, BitAnd, Div, Mul, Rem, Shr, Sub};
+
+use crate::{PrimeFactorSieve, SieveInt};
+
+/// Bases for which the Miller–Rabin test is deterministic for all n < 2^64.
+const U64_WITNESSES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Bases used for the Miller–Rabin test of n >= 2^64. No composite is known to
+/// be a strong probable prime to all of them.
+const U128_WITNESSES: [u64; 20] = [
+  2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+/// An unsigned integer type supporting the modular arithmetic needed for
+/// Miller–Rabin and Pollard's rho.
+pub(crate) trait ModInt:
+  Copy
+  + Ord
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Rem<Output = Self>
+  + BitAnd<Output = Self>
+  + Shr<u32, Output = Self>
+{
+  const ZERO: Self;
+  const ONE: Self;
+
+  fn from_u64(n: u64) -> Self;
+
+  fn as_u128(self) -> u128;
+
+  fn trailing_zeros(self) -> u32;
+
+  /// Returns `a * b mod m`, for a, b < m.
+  fn mul_mod(a: Self, b: Self, m: Self) -> Self;
+
+  /// Returns `a + b mod m`, for a, b < m.
+  fn add_mod(a: Self, b: Self, m: Self) -> Self {
+    if a >= m - b { a - (m - b) } else { a + b }
+  }
+
+  fn pow_mod(mut base: Self, mut exp: Self, m: Self) -> Self {
+    let mut result = Self::ONE % m;
+    base = base % m;
+    while exp != Self::ZERO {
+      if exp & Self::ONE != Self::ZERO {
+        result = Self::mul_mod(result, base, m);
+      }
+      base = Self::mul_mod(base, base, m);
+      exp = exp >> 1;
+    }
+    result
+  }
+
+  fn gcd(mut a: Self, mut b: Self) -> Self {
+    while b != Self::ZERO {
+      (a, b) = (b, a % b);
+    }
+    a
+  }
+}
+
+impl ModInt for u64 {
+  const ZERO: Self = 0;
+  const ONE: Self = 1;
+
+  fn from_u64(n: u64) -> Self {
+    n
+  }
+
+  fn as_u128(self) -> u128 {
+    self as u128
+  }
+
+  fn trailing_zeros(self) -> u32 {
+    u64::trailing_zeros(self)
+  }
+
+  fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+  }
+}
+
+impl ModInt for u128 {
+  const ZERO: Self = 0;
+  const ONE: Self = 1;
+
+  fn from_u64(n: u64) -> Self {
+    n as u128
+  }
+
+  fn as_u128(self) -> u128 {
+    self
+  }
+
+  fn trailing_zeros(self) -> u32 {
+    u128::trailing_zeros(self)
+  }
+
+  fn mul_mod(mut a: u128, mut b: u128, m: u128) -> u128 {
+    if let Ok(m) = u64::try_from(m) {
+      return u64::mul_mod(a as u64, b as u64, m) as u128;
+    }
+    if let Some(product) = a.checked_mul(b) {
+      return product % m;
+    }
+
+    // Double-and-add, which never overflows since all terms are < m.
+    let mut result = 0;
+    while b != 0 {
+      if b & 1 != 0 {
+        result = Self::add_mod(result, a, m);
+      }
+      a = Self::add_mod(a, a, m);
+      b >>= 1;
+    }
+    result
+  }
+}
+
+/// Returns whether odd n > 2 is a strong probable prime to base a, where
+/// n - 1 = d * 2^s with d odd.
+fn is_strong_probable_prime<T: ModInt>(n: T, d: T, s: u32, a: T) -> bool {
+  let mut x = T::pow_mod(a, d, n);
+  if x == T::ONE || x == n - T::ONE {
+    return true;
+  }
+  for _ in 1..s {
+    x = T::mul_mod(x, x, n);
+    if x == n - T::ONE {
+      return true;
+    }
+  }
+  false
+}
+
+fn miller_rabin<T: ModInt>(n: T, witnesses: &[u64]) -> bool {
+  if n < T::from_u64(2) {
+    return false;
+  }
+  if let Some(&p) = witnesses.iter().find(|&&p| n % T::from_u64(p) == T::ZERO) {
+    return n == T::from_u64(p);
+  }
+
+  let s = (n - T::ONE).trailing_zeros();
+  let d = (n - T::ONE) >> s;
+  witnesses
+    .iter()
+    .all(|&a| is_strong_probable_prime(n, d, s, T::from_u64(a)))
+}
+
+/// Returns whether n is prime, using a Miller–Rabin test with a witness set
+/// which is deterministic for all u64.
+pub fn is_prime_miller_rabin(n: u64) -> bool {
+  miller_rabin(n, &U64_WITNESSES)
+}
+
+/// Returns whether n is probably prime. Deterministic for n < 2^64, and
+/// otherwise a Miller–Rabin test with the first 20 primes as witnesses.
+pub fn is_probable_prime_u128(n: u128) -> bool {
+  is_probable_prime(n)
+}
+
+pub(crate) fn is_probable_prime<T: ModInt>(n: T) -> bool {
+  match u64::try_from(n.as_u128()) {
+    Ok(n) => is_prime_miller_rabin(n),
+    Err(_) => miller_rabin(n, &U128_WITNESSES),
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns whether n is prime. Answers from the table if n is within the
+  /// bound of the sieve, and otherwise falls back to a deterministic
+  /// Miller–Rabin test.
+  pub fn is_prime_u64(&self, n: u64) -> bool {
+    if n > self.bound().as_u64() {
+      is_prime_miller_rabin(n)
+    } else {
+      n >= 2 && self.is_prime(Q::from_u64(n))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::PrimeFactorSieve;
+
+  use super::{ModInt, is_prime_miller_rabin, is_probable_prime_u128};
+
+  #[test]
+  fn test_pow_mod() {
+    assert_eq!(u64::pow_mod(2, 10, 1000), 24);
+    assert_eq!(u64::pow_mod(3, 0, 7), 1);
+    assert_eq!(u64::pow_mod(5, 3, 1), 0);
+    assert_eq!(u64::pow_mod(u64::MAX - 1, 2, u64::MAX), 1);
+  }
+
+  #[test]
+  fn test_mul_mod_u128() {
+    let m = u128::MAX - 158;
+    assert_eq!(u128::mul_mod(m - 1, m - 1, m), 1);
+    assert_eq!(u128::mul_mod(m - 1, 2, m), m - 2);
+    assert_eq!(
+      u128::mul_mod(1 << 100, 1 << 100, (1 << 127) - 1),
+      1 << (200 - 127)
+    );
+  }
+
+  #[test]
+  fn test_matches_sieve() {
+    let sieve = PrimeFactorSieve::new(1_000_000);
+    for n in 0..=1_000_000 {
+      assert_eq!(is_prime_miller_rabin(n), n >= 2 && sieve.is_prime(n as u32));
+    }
+  }
+
+  #[test]
+  fn test_large() {
+    // Mersenne primes and the largest prime below 2^64.
+    assert!(is_prime_miller_rabin((1 << 31) - 1));
+    assert!(is_prime_miller_rabin((1 << 61) - 1));
+    assert!(is_prime_miller_rabin(18_446_744_073_709_551_557));
+    assert!(!is_prime_miller_rabin(u64::MAX));
+    assert!(!is_prime_miller_rabin(((1 << 31) - 1) * ((1 << 31) - 1)));
+  }
+
+  #[test]
+  fn test_large_u128() {
+    assert!(is_probable_prime_u128((1 << 89) - 1));
+    assert!(is_probable_prime_u128((1 << 127) - 1));
+    assert!(is_probable_prime_u128(u128::MAX - 158));
+    assert!(!is_probable_prime_u128(u128::MAX));
+    assert!(!is_probable_prime_u128(((1 << 61) - 1) * ((1 << 61) - 1)));
+    assert!(!is_probable_prime_u128(18_446_744_073_709_551_557 * 3));
+  }
+
+  #[test]
+  fn test_pseudoprimes() {
+    // Carmichael numbers.
+    for n in [561, 1105, 1729, 2465, 2821, 6601, 8911] {
+      assert!(!is_prime_miller_rabin(n));
+    }
+    // Strong pseudoprimes to the first several prime bases.
+    assert!(!is_prime_miller_rabin(3_215_031_751));
+    assert!(!is_prime_miller_rabin(3_474_749_660_383));
+    assert!(!is_prime_miller_rabin(341_550_071_728_321));
+    assert!(!is_prime_miller_rabin(3_825_123_056_546_413_051));
+  }
+
+  #[test]
+  fn test_sieve_fallback() {
+    let sieve = PrimeFactorSieve::new(100);
+    assert!(!sieve.is_prime_u64(0));
+    assert!(!sieve.is_prime_u64(1));
+    assert!(sieve.is_prime_u64(97));
+    assert!(!sieve.is_prime_u64(100));
+    assert!(sieve.is_prime_u64(101));
+    assert!(!sieve.is_prime_u64(561));
+    assert!(sieve.is_prime_u64(1_000_000_007));
+  }
+}
@@ -0,0 +1,157 @@
+use crate::{PrimeSieve, SieveError};
+
+/// A primality table storing a single bit per odd integer, for callers which
+/// don't need factorizations. Uses 1/64th the memory of a `PrimeFactorSieve`
+/// storing `u32`s.
+pub struct PrimeBitSieve {
+  /// Bit i of word j is set if `2 * (64 * j + i) + 1` is prime.
+  odd_primes: Vec<u64>,
+  bound: u64,
+}
+
+impl PrimeBitSieve {
+  pub fn new(n: u64) -> Self {
+    let odd_count = usize::try_from(n.div_ceil(2)).expect("Sieve bound must fit in usize");
+    let mut odd_primes = vec![u64::MAX; odd_count.div_ceil(64)];
+    if let Some(last) = odd_primes.last_mut() {
+      // Clear the bits past the end of the table, so primes() stops at n.
+      if !odd_count.is_multiple_of(64) {
+        *last &= (1 << (odd_count % 64)) - 1;
+      }
+      // 1 is not prime.
+      odd_primes[0] &= !1;
+    }
+
+    let n = n as usize;
+    for p in (3..).step_by(2).take_while(|p| p * p <= n) {
+      if odd_primes[p / 128] & (1 << ((p / 2) % 64)) == 0 {
+        continue;
+      }
+
+      for j in (p * p..=n).step_by(2 * p) {
+        odd_primes[j / 128] &= !(1 << ((j / 2) % 64));
+      }
+    }
+
+    Self { odd_primes, bound: n as u64 }
+  }
+
+  /// Returns the largest number covered by this sieve.
+  pub fn bound(&self) -> u64 {
+    self.bound
+  }
+
+  /// Returns whether n is prime, where 0 and 1 are not prime. Panics if n is
+  /// out of range.
+  pub fn is_prime(&self, n: u64) -> bool {
+    self.try_is_prime(n).unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_is_prime(&self, n: u64) -> Result<bool, SieveError> {
+    if n > self.bound {
+      return Err(SieveError::OutOfRange { n, max: self.bound });
+    }
+    if n.is_multiple_of(2) {
+      return Ok(n == 2);
+    }
+    let n = n as usize;
+    Ok(self.odd_primes[n / 128] & (1 << ((n / 2) % 64)) != 0)
+  }
+
+  /// Returns an iterator over all primes <= bound, in ascending order.
+  pub fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+    (self.bound >= 2)
+      .then_some(2)
+      .into_iter()
+      .chain(self.odd_primes.iter().enumerate().flat_map(|(j, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+          (word != 0).then(|| {
+            let i = word.trailing_zeros() as u64;
+            word &= word - 1;
+            2 * (64 * j as u64 + i) + 1
+          })
+        })
+      }))
+  }
+}
+
+impl PrimeSieve for PrimeBitSieve {
+  type Int = u64;
+
+  fn bound(&self) -> u64 {
+    PrimeBitSieve::bound(self)
+  }
+
+  fn is_prime(&self, n: u64) -> bool {
+    PrimeBitSieve::is_prime(self, n)
+  }
+
+  fn primes(&self) -> impl Iterator<Item = u64> + '_ {
+    PrimeBitSieve::primes(self)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeSieve, SieveError, SieveInt};
+
+  use super::PrimeBitSieve;
+
+  fn prime_sum<P: PrimeSieve>(sieve: &P) -> u64 {
+    sieve.primes().map(|p| p.as_u64()).sum()
+  }
+
+  #[test]
+  fn test_primes() {
+    let sieve = PrimeBitSieve::new(100);
+    assert_eq!(
+      sieve.primes().collect_vec(),
+      vec![
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+        97
+      ]
+    );
+  }
+
+  #[test]
+  fn test_matches_factor_sieve() {
+    for n in (0..300).chain([100_000, 100_001]) {
+      let sieve = PrimeFactorSieve::new(n);
+      let bits = PrimeBitSieve::new(n as u64);
+      assert_eq!(bits.bound(), n as u64);
+      assert!(bits.primes().eq(sieve.primes().map(u64::from)));
+      for m in 2..=n {
+        assert_eq!(bits.is_prime(m as u64), sieve.is_prime(m));
+      }
+    }
+  }
+
+  #[test]
+  fn test_common_trait() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    let bits = PrimeBitSieve::new(10_000);
+    assert_eq!(prime_sum(&sieve), prime_sum(&bits));
+    assert_eq!(PrimeSieve::bound(&sieve) as u64, PrimeSieve::bound(&bits));
+  }
+
+  #[test]
+  #[should_panic(expected = "101 is out of range")]
+  fn test_out_of_range() {
+    PrimeBitSieve::new(100).is_prime(101);
+  }
+
+  #[test]
+  fn test_try_is_prime() {
+    let sieve = PrimeBitSieve::new(100);
+    assert_eq!(sieve.try_is_prime(0), Ok(false));
+    assert_eq!(sieve.try_is_prime(1), Ok(false));
+    assert_eq!(sieve.try_is_prime(97), Ok(true));
+    assert_eq!(
+      sieve.try_is_prime(102),
+      Err(SieveError::OutOfRange { n: 102, max: 100 })
+    );
+  }
+}
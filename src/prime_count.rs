@@ -0,0 +1,71 @@
+use crate::{CompletelyMultiplicative, PrimeFactorSieve, SieveInt};
+
+/// The constant function f(n) = 1, whose sum over primes is π(x).
+struct PrimeCounter;
+
+impl CompletelyMultiplicative for PrimeCounter {
+  type Output = u64;
+
+  fn value(&self, _n: u64) -> u64 {
+    1
+  }
+
+  fn sum_from_2(&self, v: u64) -> u64 {
+    v.saturating_sub(1)
+  }
+
+  fn add(&self, a: u64, b: u64) -> u64 {
+    a + b
+  }
+
+  fn sub(&self, a: u64, b: u64) -> u64 {
+    a - b
+  }
+
+  fn mul(&self, a: u64, b: u64) -> u64 {
+    a * b
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns π(x), the number of primes <= x, for any x.
+  ///
+  /// If x is within the bound the primes are counted with the sieve's rank
+  /// index. Otherwise this uses the Lucy_Hedgehog algorithm in O(x^(3/4)) time
+  /// and O(sqrt(x)) memory, see `prime_sum_of`.
+  pub fn prime_count(&self, x: u64) -> u64 {
+    if x <= self.bound().as_u64() {
+      return self.rank(x as usize);
+    }
+    self.prime_sum_of(x, &PrimeCounter)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::PrimeFactorSieve;
+
+  #[test]
+  fn test_prime_count_small() {
+    let sieve = PrimeFactorSieve::new(100_000);
+    let small = PrimeFactorSieve::new(10);
+    let mut count = 0;
+    for x in 0..=100_000 {
+      if sieve.is_prime(x) {
+        count += 1;
+      }
+      if x <= 1_000 || x % 89 == 0 {
+        assert_eq!(sieve.prime_count(x as u64), count);
+        assert_eq!(small.prime_count(x as u64), count);
+      }
+    }
+  }
+
+  #[test]
+  fn test_prime_count_large() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.prime_count(1_000_000), 78_498);
+    assert_eq!(sieve.prime_count(1_000_000_000), 50_847_534);
+    assert_eq!(sieve.prime_count(10_000_000_000), 455_052_511);
+  }
+}
@@ -1,99 +1,305 @@
+use std::{marker::PhantomData, sync::OnceLock};
+
 use either::Either;
 
-pub struct PrimeFactorSieve {
-  smallest_prime_factors: Vec<u32>,
+use crate::{
+  PrimeFactorSieveBuilder, SieveAlgorithm, SieveError, SieveInt, SpfLayout,
+  prime_index::PrimeIndex,
+  sieve_builder::linear_table,
+  spf_table::{FullWheel, SpfTable},
+};
+
+/// A table of the smallest prime factor of every integer up to some bound.
+///
+/// Table entries are stored as `S`, while queries take and return `Q`, so e.g.
+/// a `PrimeFactorSieve<u16, u64>` stores 2 bytes per entry but can compute
+/// products which do not fit in a `u16`.
+pub struct PrimeFactorSieve<S = u32, Q = S> {
+  smallest_prime_factors: SpfTable<S>,
+  /// μ(n) for all n <= bound, if requested at construction.
+  mobius: Option<Vec<i8>>,
+  /// All primes <= bound, if requested at construction.
+  primes: Option<Vec<Q>>,
+  /// Rank/select index over the primes, built on first use.
+  prime_index: OnceLock<PrimeIndex>,
+  query: PhantomData<fn(Q) -> Q>,
 }
 
 impl PrimeFactorSieve {
   pub fn new(n: u32) -> Self {
+    PrimeFactorSieveBuilder::new(n as u64).build()
+  }
+
+  /// Constructs the same sieve as `new` using a linear (Euler) sieve, which
+  /// writes each entry exactly once.
+  pub fn new_linear(n: u32) -> Self {
+    PrimeFactorSieveBuilder::new(n as u64)
+      .algorithm(SieveAlgorithm::Linear)
+      .build()
+  }
+
+  /// Like `new_linear`, but also returns the list of all primes <= n, which is
+  /// collected as a by-product of the linear sieve.
+  pub fn new_linear_with_primes(n: u32) -> (Self, Vec<u32>) {
     let n = n as usize;
-    let mut v = vec![0; n + 1];
-    for i in 2..=n {
-      if v[i] != 0 {
-        continue;
-      }
+    let (entries, primes) = linear_table::<_, FullWheel>(n);
+    (
+      Self::from_table(SpfTable::new(SpfLayout::Full, n, entries)),
+      primes,
+    )
+  }
 
-      for j in (i..=n).step_by(i) {
-        if v[j] == 0 {
-          v[j] = i as u32;
-        }
-      }
+  /// Constructs the same sieve as `new`, filling the table concurrently. Uses
+  /// rayon's global thread pool if the `rayon` feature is enabled, otherwise
+  /// one std thread per available core.
+  pub fn new_parallel(n: u32) -> Self {
+    PrimeFactorSieveBuilder::new(n as u64)
+      .algorithm(SieveAlgorithm::Parallel)
+      .build()
+  }
+
+  /// Constructs the same sieve as `new`, splitting the table into contiguous
+  /// ranges which are filled by `threads` std threads.
+  pub fn new_parallel_with_threads(n: u32, threads: usize) -> Self {
+    PrimeFactorSieveBuilder::new(n as u64)
+      .algorithm(SieveAlgorithm::Parallel)
+      .threads(threads)
+      .build()
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  pub(crate) fn from_table(smallest_prime_factors: SpfTable<S>) -> Self {
+    Self {
+      smallest_prime_factors,
+      mobius: None,
+      primes: None,
+      prime_index: OnceLock::new(),
+      query: PhantomData,
+    }
+  }
+
+  pub(crate) fn set_mobius_table(&mut self, mobius: Vec<i8>) {
+    self.mobius = Some(mobius);
+  }
+
+  pub(crate) fn set_prime_list(&mut self, primes: Vec<Q>) {
+    self.primes = Some(primes);
+  }
+
+  /// Returns the smallest prime factor of n <= bound, or 0 if n < 2, without
+  /// checking the input.
+  pub(crate) fn spf(&self, n: usize) -> usize {
+    self.smallest_prime_factors.get(n)
+  }
+
+  /// Splits 2 <= n <= bound into n = p^e * m, with p the smallest prime factor
+  /// of n, and returns (p, e, m). Tables of multiplicative functions fill in
+  /// n from f(p^e) and the earlier entry for m.
+  pub(crate) fn split_prime_power(&self, n: usize) -> (usize, u32, usize) {
+    let p = self.spf(n);
+    let mut m = n / p;
+    let mut e = 1;
+    while self.spf(m) == p {
+      m /= p;
+      e += 1;
+    }
+    (p, e, m)
+  }
+
+  /// Returns the rank/select index over the primes, building it on first use.
+  pub(crate) fn prime_index(&self) -> &PrimeIndex {
+    self.prime_index.get_or_init(|| {
+      PrimeIndex::new(
+        self.smallest_prime_factors.bound(),
+        self.smallest_prime_factors.primes(),
+      )
+    })
+  }
+
+  /// Returns the rank/select index over the primes, if it has been built.
+  pub(crate) fn built_prime_index(&self) -> Option<&PrimeIndex> {
+    self.prime_index.get()
+  }
+
+  /// Returns the largest number covered by this sieve.
+  pub fn bound(&self) -> Q {
+    Q::from_usize(self.smallest_prime_factors.bound())
+  }
+
+  /// Returns μ(n) for all n <= bound (with μ(0) = 0), if the sieve was built
+  /// with `PrimeFactorSieveBuilder::store_mobius`.
+  pub fn mobius_table(&self) -> Option<&[i8]> {
+    self.mobius.as_deref()
+  }
+
+  /// Returns all primes <= bound in ascending order, if the sieve was built
+  /// with `PrimeFactorSieveBuilder::store_primes`.
+  pub fn prime_list(&self) -> Option<&[Q]> {
+    self.primes.as_deref()
+  }
+
+  /// Returns how the smallest-prime-factor table is stored.
+  pub fn layout(&self) -> SpfLayout {
+    self.smallest_prime_factors.layout()
+  }
+
+  /// Returns an error if n is larger than the bound of the sieve.
+  pub(crate) fn check_in_range(&self, n: Q) -> Result<(), SieveError> {
+    if n > self.bound() {
+      Err(SieveError::OutOfRange {
+        n: n.as_u64(),
+        max: self.bound().as_u64(),
+      })
+    } else {
+      Ok(())
+    }
+  }
+
+  /// Returns an error if n is not a valid input to a factorization query.
+  pub(crate) fn check_factorable(&self, n: Q) -> Result<(), SieveError> {
+    if n == Q::ZERO {
+      Err(SieveError::Zero)
+    } else {
+      self.check_in_range(n)
     }
+  }
+
+  /// Returns whether n is prime, where 0 and 1 are not prime. Panics if n is
+  /// out of range.
+  pub fn is_prime(&self, n: Q) -> bool {
+    self.try_is_prime(n).unwrap_or_else(|err| panic!("{err}"))
+  }
 
-    Self { smallest_prime_factors: v }
+  pub fn try_is_prime(&self, n: Q) -> Result<bool, SieveError> {
+    self.check_in_range(n)?;
+    Ok(self.smallest_prime_factors.get(n.as_usize()) == n.as_usize() && n != Q::ZERO)
   }
 
-  pub fn is_prime(&self, n: u32) -> bool {
-    debug_assert!(n >= 2);
-    self.smallest_prime_factors[n as usize] == n
+  /// Returns the smallest prime factor of n. Panics if n is 0, 1 or out of
+  /// range.
+  pub fn smallest_prime_factor(&self, n: Q) -> Q {
+    self
+      .try_smallest_prime_factor(n)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_smallest_prime_factor(&self, n: Q) -> Result<Q, SieveError> {
+    self.check_factorable(n)?;
+    if n == Q::ONE {
+      return Err(SieveError::One);
+    }
+    Ok(Q::from_usize(self.smallest_prime_factors.get(n.as_usize())))
+  }
+
+  /// Returns an iterator over all primes, which iterates over the stored prime
+  /// list if there is one, and otherwise scans the table.
+  pub fn primes(&self) -> impl Iterator<Item = Q> {
+    match self.prime_list() {
+      Some(primes) => Either::Left(primes.iter().copied()),
+      None => Either::Right(self.smallest_prime_factors.primes().map(Q::from_usize)),
+    }
   }
 
-  /// Returns an iterator over all primes.
-  pub fn primes(&self) -> impl Iterator<Item = u32> {
+  /// Returns an iterator over prime factors (p, multiplicity). Panics if n is 0
+  /// or out of range.
+  pub fn prime_factors(&self, n: Q) -> impl Iterator<Item = (Q, u32)> + Clone {
     self
-      .smallest_prime_factors
-      .iter()
-      .enumerate()
-      .skip(2)
-      .filter_map(|(p, &spf)| (spf == p as u32).then_some(p as u32))
+      .try_prime_factors(n)
+      .unwrap_or_else(|err| panic!("{err}"))
   }
 
-  /// Returns an iterator over prime factors (p, multiplicity).
-  pub fn prime_factors(&self, n: u32) -> impl Iterator<Item = (u32, u32)> + Clone {
-    let mut n = n as usize;
-    debug_assert_ne!(n, 0);
-    debug_assert!(n <= self.smallest_prime_factors.len());
+  pub fn try_prime_factors(
+    &self,
+    n: Q,
+  ) -> Result<impl Iterator<Item = (Q, u32)> + Clone, SieveError> {
+    self.check_factorable(n)?;
 
-    std::iter::from_fn(move || {
+    let mut n = n.as_usize();
+    Ok(std::iter::from_fn(move || {
       (n != 1).then(|| {
-        let p = self.smallest_prime_factors[n];
+        let p = self.smallest_prime_factors.get(n);
         let mut count = 1;
-        n /= p as usize;
-        while self.smallest_prime_factors[n] == p {
-          n /= p as usize;
+        n /= p;
+        while self.smallest_prime_factors.get(n) == p {
+          n /= p;
           count += 1;
         }
 
-        (p, count)
+        (Q::from_usize(p), count)
       })
-    })
+    }))
   }
 
-  /// Returns the number of factors this number has.
-  pub fn factors_count(&self, n: u32) -> u32 {
-    self.prime_factors(n).map(|(_, pow)| pow + 1).product()
+  /// Returns an iterator over prime factors (p, multiplicity) of any n up to
+  /// bound^2. The part of n above the bound is factored by trial division with
+  /// `primes()`, and the rest with the table.
+  pub fn prime_factors_extended(
+    &self,
+    n: u64,
+  ) -> Result<impl Iterator<Item = (u64, u32)>, SieveError> {
+    let bound = self.bound().as_u64();
+    let max = bound.saturating_mul(bound);
+    if n == 0 {
+      return Err(SieveError::Zero);
+    }
+    if n > max {
+      return Err(SieveError::OutOfRange { n, max });
+    }
+
+    let mut n = n;
+    let mut primes = self.primes().map(Q::as_u64);
+    Ok(std::iter::from_fn(move || {
+      if n == 1 {
+        return None;
+      }
+      if n <= bound {
+        let (p, count) = self.prime_factors(Q::from_u64(n)).next()?;
+        n /= p.as_u64().pow(count);
+        return Some((p.as_u64(), count));
+      }
+
+      for p in primes.by_ref() {
+        if p.checked_mul(p).is_none_or(|p2| p2 > n) {
+          break;
+        }
+        if n.is_multiple_of(p) {
+          let mut count = 0;
+          while n.is_multiple_of(p) {
+            n /= p;
+            count += 1;
+          }
+          return Some((p, count));
+        }
+      }
+
+      // No prime <= sqrt(n) divides n, so it must be prime.
+      Some((std::mem::replace(&mut n, 1), 1))
+    }))
   }
 
-  fn factors_generator<'a>(
-    &'a self,
-    multiplier: u32,
-    mut prime_factors: impl Iterator<Item = (u32, u32)> + Clone + 'a,
-  ) -> impl Iterator<Item = u32> {
-    match prime_factors.next() {
-      Some((p, m)) => Either::Left(
-        std::iter::successors(Some((1, 0)), move |&(n, pow)| {
-          (pow < m).then(|| (n * p, pow + 1))
-        })
-        .flat_map(move |(p, _)| {
-          Box::new(self.factors_generator(p * multiplier, prime_factors.clone()))
-            as Box<dyn Iterator<Item = u32>>
-        }),
-      ),
-      None => Either::Right(std::iter::once(multiplier)),
-    }
+  /// Returns the number of factors this number has. Panics if n is 0 or out of
+  /// range.
+  pub fn factors_count(&self, n: Q) -> Q {
+    self
+      .prime_factors(n)
+      .map(|(_, pow)| Q::from_u64(pow as u64 + 1))
+      .product()
   }
 
-  pub fn factors(&self, n: u32) -> impl Iterator<Item = u32> {
-    self.factors_generator(1, self.prime_factors(n))
+  pub fn try_factors_count(&self, n: Q) -> Result<Q, SieveError> {
+    self.check_factorable(n)?;
+    Ok(self.factors_count(n))
   }
 
-  pub fn coprime(&self, a: u32, b: u32) -> bool {
+  /// Returns whether a and b share no prime factors. Panics if either is 0 or
+  /// out of range.
+  pub fn coprime(&self, a: Q, b: Q) -> bool {
     let mut a_i = self.prime_factors(a);
     let mut b_i = self.prime_factors(b);
     let mut ps = a_i
       .next()
-      .map(|(ap, _)| b_i.next().map(|(bp, _)| (ap, bp)))
-      .flatten();
+      .and_then(|(ap, _)| b_i.next().map(|(bp, _)| (ap, bp)));
 
     while let Some((ap, bp)) = ps {
       if ap == bp {
@@ -108,12 +314,24 @@ impl PrimeFactorSieve {
     true
   }
 
-  pub fn totient(&self, n: u32) -> u32 {
+  pub fn try_coprime(&self, a: Q, b: Q) -> Result<bool, SieveError> {
+    self.check_factorable(a)?;
+    self.check_factorable(b)?;
+    Ok(self.coprime(a, b))
+  }
+
+  /// Returns Euler's totient of n. Panics if n is 0 or out of range.
+  pub fn totient(&self, n: Q) -> Q {
     let (n, q) = self
       .prime_factors(n)
-      .fold((n, 1), |(n, q), (p, _)| (n / p, q * (p - 1)));
+      .fold((n, Q::ONE), |(n, q), (p, _)| (n / p, q * (p - Q::ONE)));
     n * q
   }
+
+  pub fn try_totient(&self, n: Q) -> Result<Q, SieveError> {
+    self.check_factorable(n)?;
+    Ok(self.totient(n))
+  }
 }
 
 #[cfg(test)]
@@ -123,6 +341,8 @@ mod tests {
   use googletest::{assert_that, prelude::unordered_elements_are};
   use itertools::Itertools;
 
+  use crate::{PrimeFactorSieveBuilder, SieveAlgorithm, SieveError, SpfLayout};
+
   use super::PrimeFactorSieve;
 
   #[test]
@@ -151,6 +371,83 @@ mod tests {
     assert!(!sieve.is_prime(10));
   }
 
+  #[test]
+  fn test_is_prime_small() {
+    let sieve = PrimeFactorSieve::new(10);
+    assert!(!sieve.is_prime(0));
+    assert!(!sieve.is_prime(1));
+    assert_eq!(sieve.try_is_prime(0), Ok(false));
+    assert_eq!(sieve.try_is_prime(1), Ok(false));
+    assert_eq!(sieve.try_is_prime(7), Ok(true));
+    assert_eq!(
+      sieve.try_is_prime(11),
+      Err(SieveError::OutOfRange { n: 11, max: 10 })
+    );
+
+    for layout in [SpfLayout::OddOnly, SpfLayout::Wheel30] {
+      let compressed: PrimeFactorSieve = PrimeFactorSieveBuilder::new(10).layout(layout).build();
+      assert!(!compressed.is_prime(0));
+      assert!(!compressed.is_prime(1));
+      assert_eq!(
+        compressed.try_is_prime(12),
+        Err(SieveError::OutOfRange { n: 12, max: 10 })
+      );
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "11 is out of range")]
+  fn test_is_prime_out_of_range() {
+    PrimeFactorSieve::new(10).is_prime(11);
+  }
+
+  #[test]
+  #[should_panic(expected = "11 is out of range")]
+  fn test_prime_factors_out_of_range() {
+    // The bound itself is in range, but one past it is not.
+    let sieve = PrimeFactorSieve::new(10);
+    assert_eq!(sieve.prime_factors(10).count(), 2);
+    let _ = sieve.prime_factors(11);
+  }
+
+  #[test]
+  fn test_checked_queries() {
+    let sieve = PrimeFactorSieve::new(10);
+    assert_eq!(sieve.try_prime_factors(0).err(), Some(SieveError::Zero));
+    assert_eq!(
+      sieve.try_prime_factors(11).err(),
+      Some(SieveError::OutOfRange { n: 11, max: 10 })
+    );
+    assert_eq!(sieve.try_prime_factors(1).unwrap().count(), 0);
+    assert_eq!(
+      sieve.try_prime_factors(6).unwrap().collect_vec(),
+      vec![(2, 1), (3, 1)]
+    );
+
+    assert_eq!(sieve.try_smallest_prime_factor(0), Err(SieveError::Zero));
+    assert_eq!(sieve.try_smallest_prime_factor(1), Err(SieveError::One));
+    assert_eq!(sieve.try_smallest_prime_factor(9), Ok(3));
+    assert_eq!(sieve.smallest_prime_factor(10), 2);
+
+    assert_eq!(sieve.try_factors_count(0), Err(SieveError::Zero));
+    assert_eq!(sieve.try_factors_count(8), Ok(4));
+    assert_eq!(sieve.try_factors(0).err(), Some(SieveError::Zero));
+    assert_eq!(sieve.try_factors(1).unwrap().collect_vec(), vec![1]);
+    assert_eq!(sieve.try_coprime(0, 3), Err(SieveError::Zero));
+    assert_eq!(
+      sieve.try_coprime(3, 20),
+      Err(SieveError::OutOfRange { n: 20, max: 10 })
+    );
+    assert_eq!(sieve.try_coprime(4, 9), Ok(true));
+    assert_eq!(sieve.try_totient(0), Err(SieveError::Zero));
+    assert_eq!(sieve.try_totient(1), Ok(1));
+    assert_eq!(sieve.try_totient(9), Ok(6));
+    assert_eq!(
+      sieve.prime_factors_extended(0).err(),
+      Some(SieveError::Zero)
+    );
+  }
+
   #[test]
   fn test_2() {
     let sieve = PrimeFactorSieve::new(2);
@@ -171,6 +468,48 @@ mod tests {
     assert_eq!(sieve.prime_factors(10).collect_vec(), vec![(2, 1), (5, 1)]);
   }
 
+  #[test]
+  fn test_prime_factors_extended() {
+    let sieve = PrimeFactorSieve::new(100);
+    for n in 1..=10_000 {
+      assert_eq!(
+        sieve.prime_factors_extended(n).unwrap().collect_vec(),
+        sieve.factorize_u64(n).collect_vec()
+      );
+    }
+    assert_eq!(
+      sieve.prime_factors_extended(97 * 97).unwrap().collect_vec(),
+      vec![(97, 2)]
+    );
+    assert_eq!(
+      sieve.prime_factors_extended(9_973).unwrap().collect_vec(),
+      vec![(9_973, 1)]
+    );
+    assert_eq!(
+      sieve.prime_factors_extended(10_001).err(),
+      Some(SieveError::OutOfRange { n: 10_001, max: 10_000 })
+    );
+  }
+
+  #[test]
+  fn test_prime_factors_extended_large() {
+    let sieve = PrimeFactorSieve::new(100_000);
+    assert_eq!(
+      sieve
+        .prime_factors_extended(99_991 * 99_989)
+        .unwrap()
+        .collect_vec(),
+      vec![(99_989, 1), (99_991, 1)]
+    );
+    assert_eq!(
+      sieve
+        .prime_factors_extended(2 * 3 * 99_991 * 9_999)
+        .unwrap()
+        .collect_vec(),
+      vec![(2, 1), (3, 3), (11, 1), (101, 1), (99_991, 1)]
+    );
+  }
+
   #[test]
   fn test_factors() {
     let sieve = PrimeFactorSieve::new(30);
@@ -189,6 +528,202 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_linear_matches_new() {
+    for n in [0, 1, 2, 3, 10, 97, 100, 1000, 65_536] {
+      let sieve = PrimeFactorSieve::new(n);
+      let (linear, primes) = PrimeFactorSieve::new_linear_with_primes(n);
+      assert_eq!(linear.smallest_prime_factors, sieve.smallest_prime_factors);
+      assert_eq!(primes, sieve.primes().collect_vec());
+    }
+  }
+
+  #[test]
+  fn test_linear_large() {
+    let sieve = PrimeFactorSieve::new(10_000_000);
+    let linear = PrimeFactorSieve::new_linear(10_000_000);
+    assert!(linear.smallest_prime_factors == sieve.smallest_prime_factors);
+  }
+
+  #[test]
+  fn test_parallel_matches_new() {
+    for n in [
+      0, 1, 2, 3, 10, 97, 100, 1000, 65_535, 65_536, 65_537, 200_000,
+    ] {
+      let sieve = PrimeFactorSieve::new(n);
+      for threads in [1, 2, 3, 8] {
+        let parallel = PrimeFactorSieve::new_parallel_with_threads(n, threads);
+        assert!(parallel.smallest_prime_factors == sieve.smallest_prime_factors);
+      }
+      let parallel = PrimeFactorSieve::new_parallel(n);
+      assert!(parallel.smallest_prime_factors == sieve.smallest_prime_factors);
+    }
+  }
+
+  #[test]
+  fn test_parallel_large() {
+    let sieve = PrimeFactorSieve::new(10_000_000);
+    let parallel = PrimeFactorSieve::new_parallel_with_threads(10_000_000, 4);
+    assert!(parallel.smallest_prime_factors == sieve.smallest_prime_factors);
+  }
+
+  #[test]
+  fn test_u16_storage() {
+    let sieve = PrimeFactorSieve::new(60_000);
+    let small: PrimeFactorSieve<u16, u64> = PrimeFactorSieveBuilder::new(60_000).build();
+    assert_eq!(small.bound(), 60_000);
+    assert!(small.primes().eq(sieve.primes().map(u64::from)));
+    for n in 1..=60_000 {
+      assert!(
+        small
+          .prime_factors(n as u64)
+          .eq(sieve.prime_factors(n).map(|(p, m)| (p as u64, m)))
+      );
+      assert_eq!(small.totient(n as u64), sieve.totient(n) as u64);
+    }
+  }
+
+  #[test]
+  fn test_u64_storage() {
+    for algorithm in [
+      SieveAlgorithm::Eratosthenes,
+      SieveAlgorithm::Linear,
+      SieveAlgorithm::Parallel,
+    ] {
+      let wide: PrimeFactorSieve<u64> = PrimeFactorSieveBuilder::new(10_000)
+        .algorithm(algorithm)
+        .build();
+      let sieve = PrimeFactorSieve::new(10_000);
+      assert!(
+        wide.smallest_prime_factors.entries().iter().copied().eq(
+          sieve
+            .smallest_prime_factors
+            .entries()
+            .iter()
+            .map(|&p| p as u64)
+        )
+      );
+      assert_that!(
+        wide.factors(720_u64).collect_vec(),
+        unordered_elements_are![
+          &1, &2, &3, &4, &5, &6, &8, &9, &10, &12, &15, &16, &18, &20, &24, &30, &36, &40, &45,
+          &48, &60, &72, &80, &90, &120, &144, &180, &240, &360, &720
+        ]
+      );
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_bound_too_large_for_storage() {
+    let _: PrimeFactorSieve<u16> = PrimeFactorSieveBuilder::new(65_536).build();
+  }
+
+  #[test]
+  fn test_compressed_layouts() {
+    let sieve = PrimeFactorSieve::new(200_000);
+    for layout in [SpfLayout::OddOnly, SpfLayout::Wheel30] {
+      for algorithm in [
+        SieveAlgorithm::Eratosthenes,
+        SieveAlgorithm::Linear,
+        SieveAlgorithm::Parallel,
+      ] {
+        let compressed: PrimeFactorSieve = PrimeFactorSieveBuilder::new(200_000)
+          .algorithm(algorithm)
+          .layout(layout)
+          .threads(3)
+          .build();
+        assert_eq!(compressed.layout(), layout);
+        assert!(compressed.primes().eq(sieve.primes()));
+        for n in 2..=200_000 {
+          assert_eq!(compressed.is_prime(n), sieve.is_prime(n));
+          assert!(compressed.prime_factors(n).eq(sieve.prime_factors(n)));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_stored_primes() {
+    let sieve = PrimeFactorSieve::new(100_000);
+    assert_eq!(sieve.prime_list(), None);
+    for layout in [SpfLayout::Full, SpfLayout::Wheel30] {
+      let stored: PrimeFactorSieve = PrimeFactorSieveBuilder::new(100_000)
+        .layout(layout)
+        .store_primes(true)
+        .build();
+      let primes = stored.prime_list().unwrap();
+      assert_eq!(primes, sieve.primes().collect_vec());
+      assert!(stored.primes().eq(sieve.primes()));
+
+      // The stored list makes range sizes exact without building the index.
+      assert_eq!(stored.primes_in(100..200).size_hint(), (21, Some(21)));
+      for k in (1..=primes.len() as u64).step_by(7) {
+        assert_eq!(stored.nth_prime(k), sieve.nth_prime(k));
+      }
+      for x in (0..=100_010).step_by(13) {
+        assert_eq!(stored.prime_count(x), sieve.prime_count(x));
+        assert_eq!(stored.next_prime(x), sieve.next_prime(x));
+        assert_eq!(stored.prev_prime(x), sieve.prev_prime(x));
+      }
+    }
+
+    // The linear sieve stores the list it collects as a by-product, to which
+    // the primes skipped by the wheel must be added.
+    for n in [0, 1, 2, 3, 4, 5, 6, 7, 30, 1_000] {
+      let reference = PrimeFactorSieve::new(n);
+      for layout in [SpfLayout::Full, SpfLayout::OddOnly, SpfLayout::Wheel30] {
+        let stored: PrimeFactorSieve = PrimeFactorSieveBuilder::new(n as u64)
+          .layout(layout)
+          .algorithm(SieveAlgorithm::Linear)
+          .store_primes(true)
+          .build();
+        assert_eq!(
+          stored.prime_list().unwrap(),
+          reference.primes().collect_vec()
+        );
+      }
+    }
+
+    let empty: PrimeFactorSieve = PrimeFactorSieveBuilder::new(1).store_primes(true).build();
+    assert_eq!(empty.prime_list(), Some(&[][..]));
+    assert_eq!(empty.nth_prime(1), 2);
+  }
+
+  #[test]
+  fn test_compressed_small_bounds() {
+    for n in 0..100 {
+      let sieve = PrimeFactorSieve::new(n);
+      for layout in [SpfLayout::OddOnly, SpfLayout::Wheel30] {
+        for builder in [
+          PrimeFactorSieveBuilder::new(n as u64),
+          PrimeFactorSieveBuilder::new(n as u64)
+            .algorithm(SieveAlgorithm::Parallel)
+            .threads(2),
+        ] {
+          let compressed: PrimeFactorSieve = builder.layout(layout).build();
+          assert_eq!(compressed.bound(), n);
+          assert!(compressed.primes().eq(sieve.primes()));
+          for m in 1..=n {
+            assert!(compressed.prime_factors(m).eq(sieve.prime_factors(m)));
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_compressed_memory() {
+    let odd: PrimeFactorSieve = PrimeFactorSieveBuilder::new(3_000)
+      .layout(SpfLayout::OddOnly)
+      .build();
+    let wheel: PrimeFactorSieve = PrimeFactorSieveBuilder::new(3_000)
+      .layout(SpfLayout::Wheel30)
+      .build();
+    assert_eq!(odd.smallest_prime_factors.entries().len(), 1_500);
+    assert_eq!(wheel.smallest_prime_factors.entries().len(), 800);
+  }
+
   #[test]
   fn test_large_factor() {
     let sieve = PrimeFactorSieve::new(100_000_000);
@@ -0,0 +1,176 @@
+use crate::{PrimeFactorSieve, SieveInt, is_prime_miller_rabin};
+
+/// The number of table entries covered by each rank sample.
+const BLOCK_SIZE: usize = 1 << 8;
+
+/// A rank/select index over the primes of a sieve, which samples the number
+/// of primes below every multiple of `BLOCK_SIZE`.
+#[derive(Clone, Debug)]
+pub(crate) struct PrimeIndex {
+  /// counts[b] is the number of primes < b * BLOCK_SIZE.
+  counts: Vec<u64>,
+}
+
+impl PrimeIndex {
+  pub(crate) fn new(bound: usize, primes: impl Iterator<Item = usize>) -> Self {
+    let mut counts = vec![0; bound / BLOCK_SIZE + 2];
+    for p in primes {
+      counts[p / BLOCK_SIZE + 1] += 1;
+    }
+    for b in 1..counts.len() {
+      counts[b] += counts[b - 1];
+    }
+    Self { counts }
+  }
+
+  /// The total number of primes in the sieve.
+  fn total(&self) -> u64 {
+    *self.counts.last().unwrap()
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns whether n <= bound is prime, without checking the input.
+  pub(crate) fn is_prime_unchecked(&self, n: usize) -> bool {
+    n >= 2 && self.spf(n) == n
+  }
+
+  /// Returns the number of primes <= n, for n <= bound. Binary searches the
+  /// stored prime list if there is one, and otherwise uses the rank index.
+  pub(crate) fn rank(&self, n: usize) -> u64 {
+    if let Some(primes) = self.prime_list() {
+      return primes.partition_point(|p| p.as_usize() <= n) as u64;
+    }
+    let block = n / BLOCK_SIZE;
+    let in_block = (block * BLOCK_SIZE..=n)
+      .filter(|&m| self.is_prime_unchecked(m))
+      .count();
+    self.prime_index().counts[block] + in_block as u64
+  }
+
+  /// Returns the k-th prime, for 1 <= k <= the number of primes in the sieve.
+  pub(crate) fn select(&self, k: u64) -> usize {
+    if let Some(primes) = self.prime_list() {
+      return primes[k as usize - 1].as_usize();
+    }
+    let counts = &self.prime_index().counts;
+    // The last block which starts with fewer than k primes below it.
+    let block = counts.partition_point(|&count| count < k) - 1;
+    (block * BLOCK_SIZE..)
+      .filter(|&m| self.is_prime_unchecked(m))
+      .nth((k - counts[block] - 1) as usize)
+      .unwrap()
+  }
+
+  /// Returns the number of primes <= bound.
+  pub(crate) fn prime_total(&self) -> u64 {
+    match self.prime_list() {
+      Some(primes) => primes.len() as u64,
+      None => self.prime_index().total(),
+    }
+  }
+
+  /// Returns the k-th prime, where the 1st prime is 2. Primes beyond the bound
+  /// are found by testing successive candidates with Miller-Rabin. Panics if
+  /// k is 0.
+  pub fn nth_prime(&self, k: u64) -> u64 {
+    assert_ne!(k, 0, "primes are numbered from 1");
+    let total = self.prime_total();
+    if k <= total {
+      return self.select(k) as u64;
+    }
+
+    let mut p = self.bound().as_u64();
+    for _ in total..k {
+      p = self
+        .next_prime(p)
+        .expect("the k-th prime does not fit in a u64");
+    }
+    p
+  }
+
+  /// Returns the smallest prime > x, or `None` if it does not fit in a `u64`.
+  pub fn next_prime(&self, x: u64) -> Option<u64> {
+    let bound = self.bound().as_u64();
+    if x < bound {
+      let rank = self.rank(x as usize);
+      if rank < self.prime_total() {
+        return Some(self.select(rank + 1) as u64);
+      }
+    }
+
+    // Multiples of small primes are rejected by Miller-Rabin without any
+    // modular exponentiation.
+    let start = x.max(bound).checked_add(1)?;
+    (start..=u64::MAX).find(|&n| is_prime_miller_rabin(n))
+  }
+
+  /// Returns the largest prime < x, or `None` if x <= 2.
+  pub fn prev_prime(&self, x: u64) -> Option<u64> {
+    let bound = self.bound().as_u64();
+    if x <= bound + 1 {
+      let rank = self.rank(x.checked_sub(1)? as usize);
+      return (rank > 0).then(|| self.select(rank) as u64);
+    }
+
+    (bound + 1..x)
+      .rev()
+      .find(|&n| is_prime_miller_rabin(n))
+      .or_else(|| self.prev_prime(bound + 1))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeFactorSieveBuilder, SpfLayout};
+
+  #[test]
+  fn test_nth_prime() {
+    for layout in [SpfLayout::Full, SpfLayout::OddOnly, SpfLayout::Wheel30] {
+      let sieve: PrimeFactorSieve = PrimeFactorSieveBuilder::new(10_000).layout(layout).build();
+      for (k, p) in sieve.primes().enumerate() {
+        assert_eq!(sieve.nth_prime(k as u64 + 1), p as u64);
+      }
+      // π(10^4) = 1229, so the rest are found beyond the bound.
+      assert_eq!(sieve.nth_prime(1_229), 9_973);
+      assert_eq!(sieve.nth_prime(1_230), 10_007);
+      assert_eq!(sieve.nth_prime(10_000), 104_729);
+    }
+    assert_eq!(PrimeFactorSieve::new(1).nth_prime(3), 5);
+  }
+
+  #[test]
+  fn test_nth_prime_large() {
+    let sieve = PrimeFactorSieve::new(16_000_000);
+    assert_eq!(sieve.nth_prime(1_000_000), 15_485_863);
+    assert_eq!(sieve.prime_count(15_485_863), 1_000_000);
+  }
+
+  #[test]
+  #[should_panic(expected = "primes are numbered from 1")]
+  fn test_nth_prime_zero() {
+    PrimeFactorSieve::new(10).nth_prime(0);
+  }
+
+  #[test]
+  fn test_next_prev_prime() {
+    let reference = PrimeFactorSieve::new(20_000);
+    let primes = reference.primes().map(|p| p as u64).collect_vec();
+    for bound in [0, 1, 2, 3, 10, 1_000] {
+      let sieve = PrimeFactorSieve::new(bound);
+      for x in 0..19_000 {
+        let next = primes.iter().copied().find(|&p| p > x);
+        let prev = primes.iter().copied().rev().find(|&p| p < x);
+        assert_eq!(sieve.next_prime(x), next, "next_prime({x})");
+        assert_eq!(sieve.prev_prime(x), prev, "prev_prime({x})");
+      }
+    }
+
+    let sieve = PrimeFactorSieve::new(100);
+    assert_eq!(sieve.next_prime(u64::MAX - 59), Some(u64::MAX - 58));
+    assert_eq!(sieve.next_prime(u64::MAX - 58), None);
+    assert_eq!(sieve.prev_prime(u64::MAX), Some(u64::MAX - 58));
+  }
+}
@@ -0,0 +1,204 @@
+use std::{
+  iter::FusedIterator,
+  ops::{Bound, RangeBounds},
+};
+
+use crate::{PrimeFactorSieve, SieveError, SieveInt};
+
+/// An iterator over the primes of a `PrimeFactorSieve` within a range, which
+/// can be iterated from either end.
+///
+/// The size hint is exact if the sieve stores its prime list or once its rank
+/// index has been built, e.g. by `nth_prime`.
+pub struct PrimesIn<'a, S, Q> {
+  sieve: &'a PrimeFactorSieve<S, Q>,
+  /// The remaining candidates are `start..end`.
+  start: usize,
+  end: usize,
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns an iterator over the primes in `range`, starting directly at the
+  /// start of the range. Panics if the end of the range is out of range.
+  pub fn primes_in(&self, range: impl RangeBounds<Q>) -> PrimesIn<'_, S, Q> {
+    self
+      .try_primes_in(range)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_primes_in(
+    &self,
+    range: impl RangeBounds<Q>,
+  ) -> Result<PrimesIn<'_, S, Q>, SieveError> {
+    let start = match range.start_bound() {
+      Bound::Included(&n) => n.as_usize(),
+      Bound::Excluded(&n) => n.as_usize().saturating_add(1),
+      Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+      Bound::Included(&n) => {
+        self.check_in_range(n)?;
+        n.as_usize() + 1
+      }
+      Bound::Excluded(&n) => {
+        if n > Q::ZERO {
+          self.check_in_range(n - Q::ONE)?;
+        }
+        n.as_usize()
+      }
+      Bound::Unbounded => self.bound().as_usize() + 1,
+    };
+    Ok(PrimesIn { sieve: self, start: start.min(end), end })
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimesIn<'_, S, Q> {
+  /// The number of primes remaining, if it can be counted without building
+  /// the rank index.
+  fn remaining(&self) -> Option<usize> {
+    (self.sieve.prime_list().is_some() || self.sieve.built_prime_index().is_some())
+      .then(|| self.count_remaining())
+  }
+
+  fn count_remaining(&self) -> usize {
+    if self.start == self.end {
+      return 0;
+    }
+    let below = match self.start {
+      0 => 0,
+      start => self.sieve.rank(start - 1),
+    };
+    (self.sieve.rank(self.end - 1) - below) as usize
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> Iterator for PrimesIn<'_, S, Q> {
+  type Item = Q;
+
+  fn next(&mut self) -> Option<Q> {
+    while self.start < self.end {
+      let n = self.start;
+      self.start += 1;
+      if self.sieve.is_prime_unchecked(n) {
+        return Some(Q::from_usize(n));
+      }
+    }
+    None
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    match self.remaining() {
+      Some(count) => (count, Some(count)),
+      None => (0, Some(self.end - self.start)),
+    }
+  }
+
+  /// Counts the remaining primes by rank if the sieve stores its prime list or
+  /// has built its rank index, and otherwise by scanning the range.
+  fn count(self) -> usize {
+    match self.remaining() {
+      Some(count) => count,
+      None => self.fold(0, |count, _| count + 1),
+    }
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> DoubleEndedIterator for PrimesIn<'_, S, Q> {
+  fn next_back(&mut self) -> Option<Q> {
+    while self.start < self.end {
+      self.end -= 1;
+      if self.sieve.is_prime_unchecked(self.end) {
+        return Some(Q::from_usize(self.end));
+      }
+    }
+    None
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> FusedIterator for PrimesIn<'_, S, Q> {}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, PrimeFactorSieveBuilder, SieveError, SpfLayout};
+
+  #[test]
+  fn test_primes_in() {
+    for layout in [SpfLayout::Full, SpfLayout::OddOnly, SpfLayout::Wheel30] {
+      let sieve: PrimeFactorSieve = PrimeFactorSieveBuilder::new(1_000).layout(layout).build();
+      let primes = sieve.primes().collect_vec();
+      for lo in (0..=1_000).step_by(37) {
+        for hi in (lo..=1_000).step_by(53) {
+          let expected = primes
+            .iter()
+            .copied()
+            .filter(|p| (lo..hi).contains(p))
+            .collect_vec();
+          assert_eq!(sieve.primes_in(lo..hi).collect_vec(), expected);
+          assert_eq!(
+            sieve.primes_in(lo..hi).rev().collect_vec(),
+            expected.iter().copied().rev().collect_vec()
+          );
+          assert_eq!(sieve.primes_in(lo..hi).count(), expected.len());
+        }
+      }
+    }
+
+    let sieve = PrimeFactorSieve::new(30);
+    assert_eq!(
+      sieve.primes_in(..).collect_vec(),
+      sieve.primes().collect_vec()
+    );
+    assert_eq!(sieve.primes_in(..=7).collect_vec(), vec![2, 3, 5, 7]);
+    assert_eq!(sieve.primes_in(23..).collect_vec(), vec![23, 29]);
+    assert_eq!(sieve.primes_in(10..=10).next(), None);
+    let (lo, hi) = (20, 10);
+    assert_eq!(sieve.primes_in(lo..hi).next(), None);
+    assert_eq!(sieve.primes_in(..0).next(), None);
+  }
+
+  #[test]
+  fn test_primes_in_both_ends() {
+    let sieve = PrimeFactorSieve::new(100);
+    let mut primes = sieve.primes_in(10..=30);
+    assert_eq!(primes.next(), Some(11));
+    assert_eq!(primes.next_back(), Some(29));
+    assert_eq!(primes.next_back(), Some(23));
+    assert_eq!(primes.next(), Some(13));
+    assert_eq!(primes.collect_vec(), vec![17, 19]);
+  }
+
+  #[test]
+  fn test_size_hint() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.primes_in(100..200).size_hint(), (0, Some(100)));
+    // Counting without the rank index scans the range rather than building it.
+    assert_eq!(sieve.primes_in(100..200).count(), 21);
+    assert!(sieve.built_prime_index().is_none());
+
+    // Building the rank index makes the size hint exact.
+    assert_eq!(sieve.nth_prime(1), 2);
+    let mut primes = sieve.primes_in(100..200);
+    assert_eq!(primes.size_hint(), (21, Some(21)));
+    primes.next();
+    primes.next_back();
+    assert_eq!(primes.size_hint(), (19, Some(19)));
+    assert_eq!(primes.count(), 19);
+  }
+
+  #[test]
+  fn test_out_of_range() {
+    let sieve = PrimeFactorSieve::new(100);
+    assert!(sieve.try_primes_in(50..=100).is_ok());
+    assert!(sieve.try_primes_in(50..101).is_ok());
+    assert_eq!(
+      sieve.try_primes_in(50..102).err(),
+      Some(SieveError::OutOfRange { n: 101, max: 100 })
+    );
+    assert_eq!(
+      sieve.try_primes_in(50..=101).err(),
+      Some(SieveError::OutOfRange { n: 101, max: 100 })
+    );
+  }
+}
@@ -0,0 +1,31 @@
+use crate::{PrimeFactorSieve, SieveInt};
+
+/// A table which can answer primality queries for all integers up to some
+/// bound.
+pub trait PrimeSieve {
+  type Int: SieveInt;
+
+  /// Returns the largest number covered by this sieve.
+  fn bound(&self) -> Self::Int;
+
+  fn is_prime(&self, n: Self::Int) -> bool;
+
+  /// Returns an iterator over all primes <= bound, in ascending order.
+  fn primes(&self) -> impl Iterator<Item = Self::Int> + '_;
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeSieve for PrimeFactorSieve<S, Q> {
+  type Int = Q;
+
+  fn bound(&self) -> Q {
+    PrimeFactorSieve::bound(self)
+  }
+
+  fn is_prime(&self, n: Q) -> bool {
+    PrimeFactorSieve::is_prime(self, n)
+  }
+
+  fn primes(&self) -> impl Iterator<Item = Q> + '_ {
+    PrimeFactorSieve::primes(self)
+  }
+}
@@ -0,0 +1,265 @@
+use crate::{PrimeBitSieve, PrimeFactorSieve, SieveInt};
+
+/// A completely multiplicative function f, with f(mn) = f(m) f(n) for all m
+/// and n, whose sum over the primes can be computed in sub-linear time.
+///
+/// The function provides its own arithmetic so that sums can be accumulated
+/// in modular arithmetic.
+pub trait CompletelyMultiplicative {
+  type Output: Copy;
+
+  /// Returns f(n).
+  fn value(&self, n: u64) -> Self::Output;
+
+  /// Returns f(2) + f(3) + ... + f(v), which is zero for v < 2.
+  fn sum_from_2(&self, v: u64) -> Self::Output;
+
+  fn add(&self, a: Self::Output, b: Self::Output) -> Self::Output;
+
+  /// Returns a - b. Only called with a >= b when using non-modular
+  /// arithmetic.
+  fn sub(&self, a: Self::Output, b: Self::Output) -> Self::Output;
+
+  fn mul(&self, a: Self::Output, b: Self::Output) -> Self::Output;
+}
+
+/// The power function f(n) = n^k, accumulated exactly in a `u128` or modulo
+/// a given modulus.
+#[derive(Clone, Debug)]
+pub struct PowerSum {
+  k: u32,
+  modulus: Option<u64>,
+  /// The Stirling numbers of the second kind S(k, j), for j <= k.
+  stirling: Vec<u128>,
+}
+
+impl PowerSum {
+  /// f(n) = n^k, summed exactly. The sums must fit in a `u128`.
+  pub fn new(k: u32) -> Self {
+    Self::with_modulus(k, None)
+  }
+
+  /// f(n) = n^k, summed modulo `modulus`.
+  pub fn new_mod(k: u32, modulus: u64) -> Self {
+    assert_ne!(modulus, 0, "modulus must be nonzero");
+    Self::with_modulus(k, Some(modulus))
+  }
+
+  fn with_modulus(k: u32, modulus: Option<u64>) -> Self {
+    let mut stirling = vec![1];
+    for i in 1..=k as usize {
+      // S(i, j) = j S(i - 1, j) + S(i - 1, j - 1).
+      let mut next = vec![0; i + 1];
+      for j in 1..=i {
+        let prev = stirling.get(j).copied().unwrap_or(0);
+        next[j] = j as u128 * prev + stirling[j - 1];
+        if let Some(m) = modulus {
+          next[j] %= m as u128;
+        }
+      }
+      stirling = next;
+    }
+    Self { k, modulus, stirling }
+  }
+
+  fn reduce(&self, n: u128) -> u128 {
+    match self.modulus {
+      Some(m) => n % m as u128,
+      None => n,
+    }
+  }
+}
+
+impl CompletelyMultiplicative for PowerSum {
+  type Output = u128;
+
+  fn value(&self, n: u64) -> u128 {
+    (0..self.k).fold(self.reduce(1), |acc, _| self.mul(acc, n as u128))
+  }
+
+  fn sum_from_2(&self, v: u64) -> u128 {
+    if v < 2 {
+      return 0;
+    }
+    if self.k == 0 {
+      return self.reduce(v as u128 - 1);
+    }
+
+    // 1^k + ... + v^k = sum_{j=1..k} S(k, j) j! C(v + 1, j + 1), where
+    // j! C(v + 1, j + 1) is the product (v + 1) v ... (v + 1 - j) / (j + 1).
+    // Exactly one of these j + 1 consecutive terms is divisible by j + 1.
+    let v = v as u128;
+    let sum = (1..=(self.k as u128).min(v)).fold(0, |sum, j| {
+      let divisible = v + 1 - (v + 1) % (j + 1);
+      let falling = (0..=j).fold(self.reduce(1), |acc, i| {
+        let term = v + 1 - i;
+        let term = if term == divisible {
+          term / (j + 1)
+        } else {
+          term
+        };
+        self.mul(acc, term)
+      });
+      self.add(sum, self.mul(self.stirling[j as usize], falling))
+    });
+    self.sub(sum, self.reduce(1))
+  }
+
+  fn add(&self, a: u128, b: u128) -> u128 {
+    self.reduce(a + b)
+  }
+
+  fn sub(&self, a: u128, b: u128) -> u128 {
+    match self.modulus {
+      Some(m) => (a + m as u128 - b) % m as u128,
+      None => a - b,
+    }
+  }
+
+  fn mul(&self, a: u128, b: u128) -> u128 {
+    match self.modulus {
+      Some(m) => (self.reduce(a) * self.reduce(b)) % m as u128,
+      None => a * b,
+    }
+  }
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns the sum of all primes <= x.
+  pub fn prime_sum(&self, x: u64) -> u128 {
+    self.prime_sum_of(x, &PowerSum::new(1))
+  }
+
+  /// Returns the sum of p^k over all primes p <= x. The sum of n^k over all
+  /// n <= x must fit in a `u128`.
+  pub fn prime_power_sum(&self, x: u64, k: u32) -> u128 {
+    self.prime_sum_of(x, &PowerSum::new(k))
+  }
+
+  /// Returns the sum of p^k over all primes p <= x, modulo `modulus`.
+  pub fn prime_power_sum_mod(&self, x: u64, k: u32, modulus: u64) -> u64 {
+    self.prime_sum_of(x, &PowerSum::new_mod(k, modulus)) as u64
+  }
+
+  /// Returns the sum of f(p) over all primes p <= x, for any x.
+  ///
+  /// If x is within the bound the primes are summed directly from the sieve.
+  /// Otherwise this uses the Lucy_Hedgehog algorithm, which runs in
+  /// O(x^(3/4)) time and O(sqrt(x)) memory by tracking the sum for all
+  /// distinct values v = x / k while sieving out each prime up to sqrt(x).
+  pub fn prime_sum_of<F: CompletelyMultiplicative>(&self, x: u64, f: &F) -> F::Output {
+    if x <= self.bound().as_u64() {
+      return self
+        .primes()
+        .take_while(|p| p.as_u64() <= x)
+        .fold(f.sum_from_2(0), |sum, p| f.add(sum, f.value(p.as_u64())));
+    }
+
+    let r = x.isqrt() as usize;
+    let base_primes = if r <= self.bound().as_usize() {
+      self
+        .primes()
+        .map(|p| p.as_usize())
+        .take_while(|&p| p <= r)
+        .collect::<Vec<_>>()
+    } else {
+      PrimeBitSieve::new(r as u64)
+        .primes()
+        .map(|p| p as usize)
+        .collect()
+    };
+
+    // small[v] holds the sum for v <= r, and large[i] the sum for x / i, for
+    // i <= r. Both start as the sum over all integers in [2, v].
+    let mut small = (0..=r as u64).map(|v| f.sum_from_2(v)).collect::<Vec<_>>();
+    let mut large = (0..=r as u64)
+      .map(|i| f.sum_from_2(x.checked_div(i).unwrap_or(0)))
+      .collect::<Vec<_>>();
+
+    for p in base_primes {
+      // Removing the numbers whose smallest prime factor is p takes away
+      // f(p) times the sum over v / p of numbers with no prime factor < p.
+      let fp = f.value(p as u64);
+      let below = small[p - 1];
+      let max_i = r.min((x / (p * p) as u64) as usize);
+      for i in 1..=max_i {
+        let d = i * p;
+        let sum = if d <= r {
+          large[d]
+        } else {
+          small[(x / d as u64) as usize]
+        };
+        large[i] = f.sub(large[i], f.mul(fp, f.sub(sum, below)));
+      }
+      // In descending order of v so that v / p is still unchanged.
+      for v in (p * p..=r).rev() {
+        small[v] = f.sub(small[v], f.mul(fp, f.sub(small[v / p], below)));
+      }
+    }
+
+    large[1]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{CompletelyMultiplicative, PrimeFactorSieve};
+
+  use super::PowerSum;
+
+  #[test]
+  fn test_power_sum() {
+    for k in 0..6 {
+      let f = PowerSum::new(k);
+      let g = PowerSum::new_mod(k, 1_000_003);
+      let mut sum = 0;
+      for v in 0..200_u64 {
+        if v >= 2 {
+          sum += (v as u128).pow(k);
+        }
+        assert_eq!(f.sum_from_2(v), sum);
+        assert_eq!(g.sum_from_2(v), sum % 1_000_003);
+      }
+    }
+  }
+
+  #[test]
+  fn test_prime_sum_small() {
+    let sieve = PrimeFactorSieve::new(20_000);
+    let small = PrimeFactorSieve::new(10);
+    let mut sums = [0_u128; 3];
+    for x in 0..=20_000 {
+      if sieve.is_prime(x) {
+        for (k, sum) in sums.iter_mut().enumerate() {
+          *sum += (x as u128).pow(k as u32);
+        }
+      }
+      if x <= 500 || x % 97 == 0 {
+        let x = x as u64;
+        assert_eq!(sieve.prime_sum(x), sums[1]);
+        assert_eq!(small.prime_sum(x), sums[1]);
+        assert_eq!(small.prime_power_sum(x, 0), small.prime_count(x) as u128);
+        assert_eq!(small.prime_power_sum(x, 2), sums[2]);
+        assert_eq!(
+          small.prime_power_sum_mod(x, 2, 1_009),
+          (sums[2] % 1_009) as u64
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn test_prime_sum_large() {
+    let sieve = PrimeFactorSieve::new(1_000);
+    assert_eq!(sieve.prime_sum(2_000_000), 142_913_828_922);
+    assert_eq!(sieve.prime_sum(1_000_000_000), 24_739_512_092_254_535);
+
+    let exact = sieve.prime_power_sum(100_000_000, 3);
+    for modulus in [1_000_000_007, u64::MAX] {
+      assert_eq!(
+        sieve.prime_power_sum_mod(100_000_000, 3, modulus),
+        (exact % modulus as u128) as u64
+      );
+    }
+  }
+}
@@ -0,0 +1,218 @@
+use std::{iter::FusedIterator, ops::Range};
+
+use crate::{Factorization, PrimeFactorSieve, SieveError, SieveInt, segmented_sieve::Segments};
+
+/// An iterator over `(n, factorization of n)` for every n in a range, in
+/// increasing order. Each segment is factored by dividing the base primes out
+/// of its multiples in place, rather than looking every n up in a table, so
+/// the range may lie far beyond the bound of the sieve.
+pub struct RangeFactorizations {
+  segments: Segments,
+  /// `remaining[i]` is the part of `segment_lo + i` not yet factored.
+  remaining: Vec<u64>,
+  /// The (index in segment, prime, exponent) of every base prime dividing an
+  /// integer of the segment, in increasing order of prime.
+  divisions: Vec<(usize, u64, u32)>,
+  /// The prime factors of the whole segment, grouped by integer, where those
+  /// of `segment_lo + i` are `factors[ends[i - 1]..ends[i]]`.
+  factors: Vec<(u64, u32)>,
+  ends: Vec<usize>,
+  pos: usize,
+}
+
+impl<S: SieveInt, Q: SieveInt> PrimeFactorSieve<S, Q> {
+  /// Returns an iterator over the factorizations of every n in `range`,
+  /// skipping 0. Panics if the sieve does not cover `sqrt(range.end)`.
+  pub fn factorize_range(&self, range: Range<u64>) -> RangeFactorizations {
+    self
+      .try_factorize_range(range)
+      .unwrap_or_else(|err| panic!("{err}"))
+  }
+
+  pub fn try_factorize_range(&self, range: Range<u64>) -> Result<RangeFactorizations, SieveError> {
+    let hi = range.end;
+    let lo = range.start.max(1).min(hi);
+    let root = self.bound().as_u64() + 1;
+    let max = root.checked_mul(root).map_or(u64::MAX, |n| n - 1);
+    if lo < hi && hi - 1 > max {
+      return Err(SieveError::OutOfRange { n: hi - 1, max });
+    }
+
+    Ok(RangeFactorizations {
+      segments: Segments::new(self, lo..hi, false),
+      remaining: Vec::new(),
+      divisions: Vec::new(),
+      factors: Vec::new(),
+      ends: Vec::new(),
+      pos: 0,
+    })
+  }
+}
+
+impl RangeFactorizations {
+  /// Sets the number of integers factored per segment.
+  pub fn segment_size(mut self, segment_size: usize) -> Self {
+    self.segments.set_segment_size(segment_size);
+    self
+  }
+
+  /// Factors the next segment, returning false once the range is exhausted.
+  fn factor_segment(&mut self) -> bool {
+    self.pos = 0;
+    self.ends.clear();
+    let Some(len) = self.segments.next_segment() else {
+      return false;
+    };
+    let segment_lo = self.segments.segment_lo();
+    self.remaining.clear();
+    self.remaining.extend(segment_lo..segment_lo + len as u64);
+    self.ends.resize(len, 0);
+
+    let (remaining, divisions, counts) = (&mut self.remaining, &mut self.divisions, &mut self.ends);
+    divisions.clear();
+    self.segments.for_each_multiple(|p, i| {
+      let mut e = 0;
+      while remaining[i].is_multiple_of(p) {
+        remaining[i] /= p;
+        e += 1;
+      }
+      divisions.push((i, p, e));
+      counts[i] += 1;
+    });
+    // Whatever is left has no prime factor <= sqrt(n), so is 1 or a prime.
+    for (count, &rest) in counts.iter_mut().zip(remaining.iter()) {
+      *count += usize::from(rest > 1);
+    }
+
+    // Turn the counts into the start of each group, then advance each start
+    // past the factors placed in its group, leaving it at the group's end.
+    let mut start = 0;
+    for count in counts.iter_mut() {
+      (*count, start) = (start, start + *count);
+    }
+    self.factors.clear();
+    self.factors.resize(start, (0, 0));
+    for &(i, p, e) in divisions.iter() {
+      self.factors[counts[i]] = (p, e);
+      counts[i] += 1;
+    }
+    for (end, &rest) in counts.iter_mut().zip(remaining.iter()) {
+      if rest > 1 {
+        self.factors[*end] = (rest, 1);
+        *end += 1;
+      }
+    }
+    true
+  }
+}
+
+impl Iterator for RangeFactorizations {
+  type Item = (u64, Factorization);
+
+  fn next(&mut self) -> Option<(u64, Factorization)> {
+    if self.pos == self.ends.len() && !self.factor_segment() {
+      return None;
+    }
+
+    let start = self.pos.checked_sub(1).map_or(0, |i| self.ends[i]);
+    let factors = self.factors[start..self.ends[self.pos]].to_vec();
+    let n = self.segments.segment_lo() + self.pos as u64;
+    self.pos += 1;
+    Some((n, Factorization::from_sorted_unchecked(factors)))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let next = self.segments.segment_lo() + self.pos as u64;
+    // Before the first segment, `segment_lo` is the start of the range.
+    let remaining = self.segments.hi() - next;
+    match usize::try_from(remaining) {
+      Ok(remaining) => (remaining, Some(remaining)),
+      Err(_) => (usize::MAX, None),
+    }
+  }
+}
+
+impl FusedIterator for RangeFactorizations {}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::{PrimeFactorSieve, SieveError, is_prime_miller_rabin};
+
+  #[test]
+  fn test_matches_sieve() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    for segment_size in [1, 7, 64, 1_000, 1 << 15] {
+      let factorizations = sieve
+        .factorize_range(0..10_001)
+        .segment_size(segment_size)
+        .collect_vec();
+      assert_eq!(factorizations.len(), 10_000);
+      for (n, f) in factorizations {
+        assert_eq!(f, sieve.factorization(n as u32), "{n}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_ranges() {
+    let sieve = PrimeFactorSieve::new(100);
+    for (lo, hi) in [
+      (0, 0),
+      (0, 1),
+      (0, 2),
+      (5, 5),
+      (10, 20),
+      (89, 98),
+      (9_000, 10_201),
+    ] {
+      let factorizations = sieve.factorize_range(lo..hi).segment_size(16).collect_vec();
+      assert_eq!(
+        factorizations.iter().map(|(n, _)| *n).collect_vec(),
+        (lo.max(1)..hi).collect_vec()
+      );
+      for (n, f) in factorizations {
+        assert_eq!(f.value(), n);
+      }
+    }
+    let mut factorizations = sieve.factorize_range(10..50).segment_size(16);
+    factorizations.by_ref().take(20).for_each(drop);
+    assert_eq!(factorizations.size_hint(), (20, Some(20)));
+    assert_eq!(factorizations.next().map(|(n, _)| n), Some(30));
+
+    let (lo, hi) = (20, 10);
+    assert_eq!(sieve.factorize_range(lo..hi).next(), None);
+  }
+
+  #[test]
+  fn test_far_range() {
+    let sieve = PrimeFactorSieve::new(1_000_000);
+    let lo = 1_000_000_000_000;
+    let mut factorizations = sieve.factorize_range(lo..lo + 10_000);
+    assert_eq!(factorizations.size_hint(), (10_000, Some(10_000)));
+    assert_eq!(
+      factorizations.next().unwrap().1.prime_factors(),
+      &[(2, 12), (5, 12)]
+    );
+    for (n, f) in factorizations {
+      assert_eq!(f.value(), n);
+      assert!(
+        f.prime_factors()
+          .iter()
+          .all(|&(p, _)| is_prime_miller_rabin(p))
+      );
+    }
+  }
+
+  #[test]
+  fn test_out_of_range() {
+    let sieve = PrimeFactorSieve::new(10);
+    assert!(sieve.try_factorize_range(100..121).is_ok());
+    assert_eq!(
+      sieve.try_factorize_range(100..122).err(),
+      Some(SieveError::OutOfRange { n: 121, max: 120 })
+    );
+    assert!(sieve.try_factorize_range(200..200).is_ok());
+  }
+}
@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use crate::{PrimeFactorSieve, SieveInt};
+
+/// Default number of integers covered by a single segment, chosen so the
+/// segment fits comfortably in L1 cache.
+const DEFAULT_SEGMENT_SIZE: usize = 1 << 15;
+
+/// The fixed-size segments of a range `[lo, hi)`, along with the base primes
+/// up to `sqrt(hi)` stepping through them, which the segmented iterators use to
+/// sieve one segment at a time.
+pub(crate) struct Segments {
+  /// All primes `p` with `p * p < hi`.
+  base_primes: Vec<u64>,
+  /// The next multiple of each base prime which has not yet been visited.
+  next_multiples: Vec<u64>,
+  segment_size: usize,
+  /// The current segment is `segment_lo..segment_hi`.
+  segment_lo: u64,
+  segment_hi: u64,
+  hi: u64,
+}
+
+impl Segments {
+  /// Takes the base primes for `range` from `sieve`, which must cover
+  /// `sqrt(range.end)`. Each base prime p is first visited at its smallest
+  /// multiple in the range, or at no less than p * p if `from_square`.
+  pub(crate) fn new<S: SieveInt, Q: SieveInt>(
+    sieve: &PrimeFactorSieve<S, Q>,
+    range: Range<u64>,
+    from_square: bool,
+  ) -> Self {
+    let Range { start: lo, end: hi } = range;
+    let base_primes = sieve
+      .primes()
+      .map(Q::as_u64)
+      .take_while(|&p| p.checked_mul(p).is_some_and(|p2| p2 < hi))
+      .collect::<Vec<_>>();
+    let next_multiples = base_primes
+      .iter()
+      .map(|&p| {
+        let first = lo.div_ceil(p) * p;
+        if from_square { first.max(p * p) } else { first }
+      })
+      .collect();
+
+    Self {
+      base_primes,
+      next_multiples,
+      segment_size: DEFAULT_SEGMENT_SIZE,
+      segment_lo: lo,
+      segment_hi: lo,
+      hi,
+    }
+  }
+
+  pub(crate) fn set_segment_size(&mut self, segment_size: usize) {
+    assert_ne!(segment_size, 0);
+    self.segment_size = segment_size;
+  }
+
+  /// The first integer of the current segment.
+  pub(crate) fn segment_lo(&self) -> u64 {
+    self.segment_lo
+  }
+
+  /// The end of the whole range.
+  pub(crate) fn hi(&self) -> u64 {
+    self.hi
+  }
+
+  /// Moves on to the next segment and returns its length, or `None` once the
+  /// range is exhausted.
+  pub(crate) fn next_segment(&mut self) -> Option<usize> {
+    self.segment_lo = self.segment_hi;
+    if self.segment_lo >= self.hi {
+      return None;
+    }
+    let len = (self.hi - self.segment_lo).min(self.segment_size as u64);
+    self.segment_hi = self.segment_lo + len;
+    Some(len as usize)
+  }
+
+  /// Calls `visit(p, i)` for each multiple `segment_lo + i` of each base prime
+  /// p in the current segment, in increasing order of p.
+  pub(crate) fn for_each_multiple(&mut self, mut visit: impl FnMut(u64, usize)) {
+    for (&p, next) in self.base_primes.iter().zip(self.next_multiples.iter_mut()) {
+      let mut j = *next;
+      while j < self.segment_hi {
+        visit(p, (j - self.segment_lo) as usize);
+        j += p;
+      }
+      *next = j;
+    }
+  }
+}
+
+/// An iterator over all primes in `[lo, hi)`, which sieves the range one
+/// fixed-size segment at a time. Memory use is bounded by the segment size plus
+/// the base primes up to `sqrt(hi)`.
+pub struct SegmentedSieve {
+  segments: Segments,
+  /// `composite[i]` is true if `segment_lo + i` is not prime.
+  composite: Vec<bool>,
+  pos: usize,
+}
+
+impl SegmentedSieve {
+  /// Constructs a segmented sieve over `range`, building a small
+  /// `PrimeFactorSieve` for the base primes.
+  pub fn new(range: Range<u64>) -> Self {
+    let root = range.end.saturating_sub(1).isqrt();
+    let root = u32::try_from(root).expect("sqrt of range end must fit in u32");
+    Self::with_base_sieve(&PrimeFactorSieve::new(root), range)
+  }
+
+  /// Constructs a segmented sieve over `range`, taking the base primes from
+  /// `sieve`, which must cover `sqrt(range.end)`.
+  pub fn with_base_sieve<S: SieveInt, Q: SieveInt>(
+    sieve: &PrimeFactorSieve<S, Q>,
+    range: Range<u64>,
+  ) -> Self {
+    let hi = range.end;
+    let lo = range.start.max(2).min(hi);
+    assert!(
+      (sieve.bound().as_u64() + 1)
+        .checked_mul(sieve.bound().as_u64() + 1)
+        .is_none_or(|n2| n2 >= hi),
+      "Base sieve of bound {} is too small for range end {hi}",
+      sieve.bound()
+    );
+
+    Self {
+      segments: Segments::new(sieve, lo..hi, true),
+      composite: Vec::new(),
+      pos: 0,
+    }
+  }
+
+  /// Sets the number of integers sieved per segment.
+  pub fn segment_size(mut self, segment_size: usize) -> Self {
+    self.segments.set_segment_size(segment_size);
+    self
+  }
+}
+
+impl Iterator for SegmentedSieve {
+  type Item = u64;
+
+  fn next(&mut self) -> Option<u64> {
+    loop {
+      if let Some(offset) = self.composite[self.pos..].iter().position(|&c| !c) {
+        self.pos += offset + 1;
+        return Some(self.segments.segment_lo() + self.pos as u64 - 1);
+      }
+
+      self.composite.clear();
+      self.pos = 0;
+      let len = self.segments.next_segment()?;
+      self.composite.resize(len, false);
+      let composite = &mut self.composite;
+      self.segments.for_each_multiple(|_, i| composite[i] = true);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use itertools::Itertools;
+
+  use crate::PrimeFactorSieve;
+
+  use super::SegmentedSieve;
+
+  fn is_prime(n: u64) -> bool {
+    n >= 2
+      && (2..)
+        .take_while(|d| d * d <= n)
+        .all(|d| !n.is_multiple_of(d))
+  }
+
+  #[test]
+  fn test_matches_sieve() {
+    let sieve = PrimeFactorSieve::new(100_000);
+    assert_eq!(
+      SegmentedSieve::new(0..100_001).collect_vec(),
+      sieve.primes().map(|p| p as u64).collect_vec()
+    );
+  }
+
+  #[test]
+  fn test_small_segments() {
+    let sieve = PrimeFactorSieve::new(10_000);
+    for segment_size in [1, 2, 7, 64, 1000] {
+      assert_eq!(
+        SegmentedSieve::new(0..10_001)
+          .segment_size(segment_size)
+          .collect_vec(),
+        sieve.primes().map(|p| p as u64).collect_vec()
+      );
+    }
+  }
+
+  #[test]
+  fn test_ranges() {
+    let sieve = PrimeFactorSieve::new(2_000);
+    for (lo, hi) in [
+      (0, 0),
+      (0, 2),
+      (2, 3),
+      (3, 3),
+      (10, 20),
+      (89, 98),
+      (1000, 2001),
+    ] {
+      assert_eq!(
+        SegmentedSieve::new(lo..hi).segment_size(16).collect_vec(),
+        sieve
+          .primes()
+          .map(|p| p as u64)
+          .filter(|p| (lo..hi).contains(p))
+          .collect_vec()
+      );
+    }
+  }
+
+  #[test]
+  fn test_far_range() {
+    let lo = 100_000_000_000;
+    let hi = lo + 10_000;
+    assert_eq!(
+      SegmentedSieve::new(lo..hi).collect_vec(),
+      (lo..hi).filter(|&n| is_prime(n)).collect_vec()
+    );
+  }
+}
@@ -0,0 +1,289 @@
+use std::thread;
+
+use crate::{
+  PrimeFactorSieve, SieveInt, SpfLayout,
+  spf_table::{FullWheel, OddWheel, SpfTable, Wheel, Wheel30},
+};
+
+/// Number of table entries filled at a time by each worker of the parallel
+/// sieve, chosen so a segment fits in L2 cache.
+const PARALLEL_SEGMENT_SIZE: usize = 1 << 16;
+
+/// The algorithm used to fill in the smallest-prime-factor table. All
+/// algorithms produce identical tables.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SieveAlgorithm {
+  /// The sieve of Eratosthenes, in O(n log log n).
+  #[default]
+  Eratosthenes,
+  /// The linear (Euler) sieve, which writes each entry exactly once.
+  Linear,
+  /// A segmented sieve of Eratosthenes, with segments filled concurrently.
+  Parallel,
+}
+
+/// Configures the construction of a `PrimeFactorSieve`.
+#[derive(Clone, Debug)]
+pub struct PrimeFactorSieveBuilder {
+  bound: u64,
+  algorithm: SieveAlgorithm,
+  layout: SpfLayout,
+  threads: Option<usize>,
+  store_mobius: bool,
+  store_primes: bool,
+}
+
+impl PrimeFactorSieveBuilder {
+  /// Constructs a builder for a sieve covering all integers <= `bound`.
+  pub fn new(bound: u64) -> Self {
+    Self {
+      bound,
+      algorithm: SieveAlgorithm::default(),
+      layout: SpfLayout::default(),
+      threads: None,
+      store_mobius: false,
+      store_primes: false,
+    }
+  }
+
+  pub fn algorithm(mut self, algorithm: SieveAlgorithm) -> Self {
+    self.algorithm = algorithm;
+    self
+  }
+
+  /// Sets how the table is stored. Compressed layouts trade a few extra
+  /// operations per lookup for 2x (`OddOnly`) or 3.75x (`Wheel30`) less memory.
+  pub fn layout(mut self, layout: SpfLayout) -> Self {
+    self.layout = layout;
+    self
+  }
+
+  /// Sets the number of std threads used by `SieveAlgorithm::Parallel`. If
+  /// unset, uses rayon's global thread pool if the `rayon` feature is enabled,
+  /// otherwise one thread per available core.
+  pub fn threads(mut self, threads: usize) -> Self {
+    self.threads = Some(threads);
+    self
+  }
+
+  /// Sets whether to compute and store μ(n) for all n <= bound, which makes
+  /// `mobius` O(1) and speeds up `mertens`, at the cost of 1 byte per integer.
+  pub fn store_mobius(mut self, store_mobius: bool) -> Self {
+    self.store_mobius = store_mobius;
+    self
+  }
+
+  /// Sets whether to store the list of all primes <= bound, which makes
+  /// `primes` a slice iterator and `nth_prime` and `prime_count` O(1) and
+  /// O(log n) within the bound, at the cost of one `Q` per prime.
+  pub fn store_primes(mut self, store_primes: bool) -> Self {
+    self.store_primes = store_primes;
+    self
+  }
+
+  /// Builds the sieve, storing table entries as `S` and answering queries in
+  /// `Q`. Panics if the bound does not fit in either type.
+  pub fn build<S: SieveInt, Q: SieveInt>(&self) -> PrimeFactorSieve<S, Q> {
+    assert!(
+      S::try_from_u64(self.bound).is_some() && Q::try_from_u64(self.bound).is_some(),
+      "Sieve bound {} does not fit in the storage or query type",
+      self.bound
+    );
+    let n = usize::try_from(self.bound).expect("Sieve bound must fit in usize");
+
+    let (entries, primes) = match self.layout {
+      SpfLayout::Full => self.build_entries::<S, FullWheel>(n),
+      SpfLayout::OddOnly => self.build_entries::<S, OddWheel>(n),
+      SpfLayout::Wheel30 => self.build_entries::<S, Wheel30>(n),
+    };
+
+    let mut sieve = PrimeFactorSieve::from_table(SpfTable::new(self.layout, n, entries));
+    if self.store_mobius {
+      let mobius = sieve.compute_mobius(n);
+      sieve.set_mobius_table(mobius);
+    }
+    if self.store_primes {
+      let primes = match primes {
+        Some(primes) => primes
+          .into_iter()
+          .map(|p| Q::from_usize(p.as_usize()))
+          .collect(),
+        None => sieve.primes().collect(),
+      };
+      sieve.set_prime_list(primes);
+    }
+    sieve
+  }
+
+  /// Builds the table entries, along with the list of all primes <= n if it
+  /// is requested and comes for free with the algorithm.
+  fn build_entries<S: SieveInt, W: Wheel>(&self, n: usize) -> (Vec<S>, Option<Vec<S>>) {
+    let entries = match (self.algorithm, self.threads) {
+      (SieveAlgorithm::Eratosthenes, _) => eratosthenes_table::<S, W>(n),
+      (SieveAlgorithm::Linear, _) => {
+        let (entries, primes) = linear_table::<S, W>(n);
+        // The linear sieve only finds the primes stored by the wheel.
+        let primes = self.store_primes.then(|| {
+          W::PRIMES
+            .iter()
+            .filter(|&&p| p <= n)
+            .map(|&p| S::from_usize(p))
+            .chain(primes)
+            .collect()
+        });
+        return (entries, primes);
+      }
+      #[cfg(feature = "rayon")]
+      (SieveAlgorithm::Parallel, None) => rayon_table::<S, W>(n),
+      (SieveAlgorithm::Parallel, threads) => parallel_table::<S, W>(
+        n,
+        threads.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get())),
+      ),
+    };
+    (entries, None)
+  }
+}
+
+/// Builds the entries of the table for the integers <= n stored by the wheel
+/// `W`, in the order they are stored.
+fn eratosthenes_table<S: SieveInt, W: Wheel>(n: usize) -> Vec<S> {
+  let len = W::count_below(n + 1);
+  let mut v = vec![S::ZERO; len];
+  for i in W::count_below(2)..len {
+    if v[i] != S::ZERO {
+      continue;
+    }
+
+    let p = W::value(i);
+    v[i] = S::from_usize(p);
+    if p.checked_mul(p).is_none_or(|p2| p2 > n) {
+      continue;
+    }
+
+    // Only multiples of p by stored integers are themselves stored.
+    for j in i.. {
+      let m = p * W::value(j);
+      if m > n {
+        break;
+      }
+      let m = W::count_below(m);
+      if v[m] == S::ZERO {
+        v[m] = S::from_usize(p);
+      }
+    }
+  }
+
+  v
+}
+
+/// Builds the entries of the table with a linear (Euler) sieve, returning them
+/// along with the list of all primes <= n stored by the wheel `W`, which is
+/// collected as a by-product.
+pub(crate) fn linear_table<S: SieveInt, W: Wheel>(n: usize) -> (Vec<S>, Vec<S>) {
+  let len = W::count_below(n + 1);
+  let mut v = vec![S::ZERO; len];
+  let mut primes = Vec::new();
+  for i in W::count_below(2)..len {
+    let x = W::value(i);
+    if v[i] == S::ZERO {
+      v[i] = S::from_usize(x);
+      primes.push(S::from_usize(x));
+    }
+
+    // Every composite x * p is visited exactly once, from its largest proper
+    // divisor x, with p ranging over the primes <= spf(x).
+    let spf = v[i];
+    for &p in primes.iter().take_while(|&&p| p <= spf) {
+      let Some(m) = x.checked_mul(p.as_usize()).filter(|&m| m <= n) else {
+        break;
+      };
+      v[W::count_below(m)] = p;
+    }
+  }
+
+  (v, primes)
+}
+
+#[cfg(feature = "rayon")]
+fn rayon_table<S: SieveInt, W: Wheel>(n: usize) -> Vec<S> {
+  use rayon::prelude::*;
+
+  let (mut v, base_primes) = parallel_base::<S, W>(n);
+  v.par_chunks_mut(PARALLEL_SEGMENT_SIZE)
+    .enumerate()
+    .for_each(|(i, segment)| {
+      fill_segment::<S, W>(segment, i * PARALLEL_SEGMENT_SIZE, &base_primes)
+    });
+  v
+}
+
+/// Builds the entries of the table by splitting them into contiguous ranges
+/// which are filled by `threads` std threads.
+fn parallel_table<S: SieveInt, W: Wheel>(n: usize, threads: usize) -> Vec<S> {
+  let threads = threads.max(1);
+  let (mut v, base_primes) = parallel_base::<S, W>(n);
+  let chunk_size = v
+    .len()
+    .div_ceil(threads)
+    .next_multiple_of(PARALLEL_SEGMENT_SIZE)
+    .max(PARALLEL_SEGMENT_SIZE);
+
+  thread::scope(|scope| {
+    for (i, chunk) in v.chunks_mut(chunk_size).enumerate() {
+      let base_primes = &base_primes;
+      scope.spawn(move || {
+        for (j, segment) in chunk.chunks_mut(PARALLEL_SEGMENT_SIZE).enumerate() {
+          let lo = i * chunk_size + j * PARALLEL_SEGMENT_SIZE;
+          fill_segment::<S, W>(segment, lo, base_primes);
+        }
+      });
+    }
+  });
+
+  v
+}
+
+/// Allocates the entries for a parallel sieve up to n, and returns them along
+/// with all primes <= sqrt(n) stored by the wheel `W`.
+fn parallel_base<S: SieveInt, W: Wheel>(n: usize) -> (Vec<S>, Vec<usize>) {
+  let base_primes = eratosthenes_table::<u64, FullWheel>(n.isqrt())
+    .into_iter()
+    .enumerate()
+    .skip(2)
+    .filter(|&(i, spf)| spf as usize == i && !W::PRIMES.contains(&i))
+    .map(|(i, _)| i)
+    .collect();
+  (vec![S::ZERO; W::count_below(n + 1)], base_primes)
+}
+
+/// Fills in the smallest prime factors of the segment of the entries starting
+/// at index `lo`, given all stored primes <= sqrt of the last integer in the
+/// segment.
+fn fill_segment<S: SieveInt, W: Wheel>(segment: &mut [S], lo: usize, base_primes: &[usize]) {
+  let lo_value = W::value(lo);
+  let hi_value = W::value(lo + segment.len() - 1);
+  for &p in base_primes {
+    if p * p > hi_value {
+      break;
+    }
+
+    for j in W::count_below(p.max(lo_value.div_ceil(p))).. {
+      let m = p * W::value(j);
+      if m > hi_value {
+        break;
+      }
+      let m = W::count_below(m) - lo;
+      if segment[m] == S::ZERO {
+        segment[m] = S::from_usize(p);
+      }
+    }
+  }
+
+  // Anything not crossed off by a prime <= sqrt(hi_value) is prime.
+  for (i, spf) in segment.iter_mut().enumerate() {
+    let n = W::value(lo + i);
+    if *spf == S::ZERO && n >= 2 {
+      *spf = S::from_usize(n);
+    }
+  }
+}
@@ -0,0 +1,107 @@
+use std::{
+  fmt::{Debug, Display},
+  hash::Hash,
+  iter::{Product, Sum},
+  ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, Sub, SubAssign},
+};
+
+/// An unsigned integer type which can be stored in or used to query a
+/// `PrimeFactorSieve`.
+pub trait SieveInt:
+  Copy
+  + Ord
+  + Hash
+  + Debug
+  + Display
+  + Default
+  + Send
+  + Sync
+  + 'static
+  + Add<Output = Self>
+  + Sub<Output = Self>
+  + Mul<Output = Self>
+  + Div<Output = Self>
+  + Rem<Output = Self>
+  + AddAssign
+  + SubAssign
+  + MulAssign
+  + DivAssign
+  + Sum
+  + Product
+{
+  const ZERO: Self;
+  const ONE: Self;
+  const MAX: Self;
+
+  /// Converts from a `usize`, truncating if it does not fit.
+  fn from_usize(n: usize) -> Self;
+
+  /// Converts to a `usize`, truncating if it does not fit.
+  fn as_usize(self) -> usize;
+
+  /// Converts from a `u64`, truncating if it does not fit.
+  fn from_u64(n: u64) -> Self;
+
+  /// Converts from a `u64`, returning `None` if it does not fit.
+  fn try_from_u64(n: u64) -> Option<Self>;
+
+  /// Converts to a `u64`, truncating if it does not fit.
+  fn as_u64(self) -> u64;
+
+  fn checked_add(self, rhs: Self) -> Option<Self>;
+
+  fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+  fn checked_pow(self, exp: u32) -> Option<Self>;
+
+  /// Converts between two `SieveInt` types, truncating if it does not fit.
+  fn cast<T: SieveInt>(self) -> T {
+    T::from_u64(self.as_u64())
+  }
+}
+
+macro_rules! impl_sieve_int {
+  ($($t:ty),*) => {
+    $(
+      impl SieveInt for $t {
+        const ZERO: Self = 0;
+        const ONE: Self = 1;
+        const MAX: Self = <$t>::MAX;
+
+        fn from_usize(n: usize) -> Self {
+          n as $t
+        }
+
+        fn as_usize(self) -> usize {
+          self as usize
+        }
+
+        fn from_u64(n: u64) -> Self {
+          n as $t
+        }
+
+        fn try_from_u64(n: u64) -> Option<Self> {
+          n.try_into().ok()
+        }
+
+        fn as_u64(self) -> u64 {
+          self as u64
+        }
+
+        fn checked_add(self, rhs: Self) -> Option<Self> {
+          <$t>::checked_add(self, rhs)
+        }
+
+        fn checked_mul(self, rhs: Self) -> Option<Self> {
+          <$t>::checked_mul(self, rhs)
+        }
+
+        fn checked_pow(self, exp: u32) -> Option<Self> {
+          <$t>::checked_pow(self, exp)
+        }
+      }
+    )*
+  };
+}
+
+impl_sieve_int!(u16, u32, u64, u128);
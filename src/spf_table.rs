@@ -0,0 +1,213 @@
+use crate::SieveInt;
+
+/// How the smallest-prime-factor table is laid out in memory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpfLayout {
+  /// One entry per integer.
+  #[default]
+  Full,
+  /// One entry per odd integer, halving memory. Even integers have smallest
+  /// prime factor 2.
+  OddOnly,
+  /// One entry per integer coprime to 2 * 3 * 5, storing 8 of every 30
+  /// integers. Every other integer is divisible by 2, 3 or 5.
+  Wheel30,
+}
+
+/// Maps the integers stored by a layout to and from their indices in the table.
+/// The stored integers are exactly those coprime to all of `PRIMES`.
+pub(crate) trait Wheel {
+  /// The primes whose multiples are not stored.
+  const PRIMES: &'static [usize];
+
+  /// Returns the number of stored integers < n, which is also the index of n if
+  /// it is stored.
+  fn count_below(n: usize) -> usize;
+
+  /// Returns the stored integer at index i.
+  fn value(i: usize) -> usize;
+}
+
+pub(crate) struct FullWheel;
+
+impl Wheel for FullWheel {
+  const PRIMES: &'static [usize] = &[];
+
+  fn count_below(n: usize) -> usize {
+    n
+  }
+
+  fn value(i: usize) -> usize {
+    i
+  }
+}
+
+pub(crate) struct OddWheel;
+
+impl Wheel for OddWheel {
+  const PRIMES: &'static [usize] = &[2];
+
+  fn count_below(n: usize) -> usize {
+    n / 2
+  }
+
+  fn value(i: usize) -> usize {
+    2 * i + 1
+  }
+}
+
+pub(crate) struct Wheel30;
+
+impl Wheel30 {
+  /// The residues mod 30 which are coprime to 30.
+  const RESIDUES: [usize; 8] = [1, 7, 11, 13, 17, 19, 23, 29];
+
+  /// `RESIDUE_COUNTS[r]` is the number of residues coprime to 30 which are < r.
+  const RESIDUE_COUNTS: [usize; 30] = {
+    let mut counts = [0; 30];
+    let mut r = 1;
+    while r < 30 {
+      let prev = Self::RESIDUES[counts[r - 1]];
+      counts[r] = counts[r - 1] + if prev < r { 1 } else { 0 };
+      r += 1;
+    }
+    counts
+  };
+}
+
+impl Wheel for Wheel30 {
+  const PRIMES: &'static [usize] = &[2, 3, 5];
+
+  fn count_below(n: usize) -> usize {
+    n / 30 * 8 + Self::RESIDUE_COUNTS[n % 30]
+  }
+
+  fn value(i: usize) -> usize {
+    i / 8 * 30 + Self::RESIDUES[i % 8]
+  }
+}
+
+/// The smallest prime factor of every integer up to `bound`, stored according
+/// to `layout`. Unstored integers are never prime (except the wheel primes
+/// themselves), and their smallest prime factor is the first wheel prime that
+/// divides them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SpfTable<S> {
+  layout: SpfLayout,
+  bound: usize,
+  entries: Vec<S>,
+}
+
+impl<S: SieveInt> SpfTable<S> {
+  pub(crate) fn new(layout: SpfLayout, bound: usize, entries: Vec<S>) -> Self {
+    debug_assert_eq!(entries.len(), layout.count_below(bound + 1));
+    Self { layout, bound, entries }
+  }
+
+  pub(crate) fn layout(&self) -> SpfLayout {
+    self.layout
+  }
+
+  pub(crate) fn bound(&self) -> usize {
+    self.bound
+  }
+
+  #[cfg(test)]
+  pub(crate) fn entries(&self) -> &[S] {
+    &self.entries
+  }
+
+  /// Returns the smallest prime factor of n, or 0 if n < 2.
+  pub(crate) fn get(&self, n: usize) -> usize {
+    match self.layout {
+      SpfLayout::Full => self.entries[n].as_usize(),
+      SpfLayout::OddOnly => self.get_in::<OddWheel>(n),
+      SpfLayout::Wheel30 => self.get_in::<Wheel30>(n),
+    }
+  }
+
+  fn get_in<W: Wheel>(&self, n: usize) -> usize {
+    match W::PRIMES.iter().find(|&&p| n.is_multiple_of(p)) {
+      Some(_) if n == 0 => 0,
+      Some(&p) => p,
+      None => self.entries[W::count_below(n)].as_usize(),
+    }
+  }
+
+  /// Returns an iterator over all primes <= bound, in ascending order.
+  pub(crate) fn primes(&self) -> impl Iterator<Item = usize> + '_ {
+    let skip = self.layout.count_below(2);
+    self
+      .layout
+      .wheel_primes()
+      .iter()
+      .copied()
+      .take_while(|&p| p <= self.bound)
+      .chain(
+        self
+          .entries
+          .iter()
+          .enumerate()
+          .skip(skip)
+          .filter_map(|(i, &spf)| {
+            let n = self.layout.value(i);
+            (spf.as_usize() == n).then_some(n)
+          }),
+      )
+  }
+}
+
+impl SpfLayout {
+  /// The primes whose multiples are not stored by this layout.
+  pub(crate) fn wheel_primes(self) -> &'static [usize] {
+    match self {
+      SpfLayout::Full => FullWheel::PRIMES,
+      SpfLayout::OddOnly => OddWheel::PRIMES,
+      SpfLayout::Wheel30 => Wheel30::PRIMES,
+    }
+  }
+
+  pub(crate) fn count_below(self, n: usize) -> usize {
+    match self {
+      SpfLayout::Full => FullWheel::count_below(n),
+      SpfLayout::OddOnly => OddWheel::count_below(n),
+      SpfLayout::Wheel30 => Wheel30::count_below(n),
+    }
+  }
+
+  pub(crate) fn value(self, i: usize) -> usize {
+    match self {
+      SpfLayout::Full => FullWheel::value(i),
+      SpfLayout::OddOnly => OddWheel::value(i),
+      SpfLayout::Wheel30 => Wheel30::value(i),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{OddWheel, Wheel, Wheel30};
+
+  fn check_wheel<W: Wheel>() {
+    let stored = (0..1000_usize)
+      .filter(|&n| W::PRIMES.iter().all(|&p| !n.is_multiple_of(p)))
+      .collect::<Vec<_>>();
+    for (i, &n) in stored.iter().enumerate() {
+      assert_eq!(W::value(i), n);
+      assert_eq!(W::count_below(n), i);
+    }
+    for n in 0..1000 {
+      assert_eq!(W::count_below(n), stored.iter().filter(|&&m| m < n).count());
+    }
+  }
+
+  #[test]
+  fn test_odd_wheel() {
+    check_wheel::<OddWheel>();
+  }
+
+  #[test]
+  fn test_wheel30() {
+    check_wheel::<Wheel30>();
+  }
+}